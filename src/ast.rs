@@ -20,7 +20,7 @@ use crate::common::JINGE_HOST_IDENT;
 //   if slot_level > 0 {
 //     Ident::from(format!("{}${}$", JINGE_EL_IDENT.sym.as_str(), slot_level))
 //   } else {
-//     JINGE_EL_IDENT.clone()
+//     JINGE_EL_IDENT.ident()
 //   }
 // }
 // #[inline]
@@ -36,7 +36,7 @@ pub fn ast_create_id_of_container(is_root_container: bool) -> Box<Expr> {
   if is_root_container {
     ast_create_expr_this()
   } else {
-    ast_create_expr_ident(JINGE_HOST_IDENT.clone())
+    ast_create_expr_ident(JINGE_HOST_IDENT.ident())
   }
 }
 #[inline]
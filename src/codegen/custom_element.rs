@@ -0,0 +1,164 @@
+use swc_core::atoms::Atom;
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::*;
+
+use crate::ast::*;
+use crate::common::*;
+use crate::parser::custom_element::PropsUsage;
+
+use super::tpl::tpl_prop_obj;
+
+/// 生成把组件注册为 Custom Element 的语句：
+/// ```js
+/// customElements.get("x-card") || customElements.define("x-card", createCustomElement$jg$(Card, {
+///   attrs: { "max-count": "maxCount" },
+///   events: { onChange: "change" },
+///   slots: [DEFAULT_SLOT$jg$, "header"],
+/// }));
+/// ```
+/// attrs 的 key 是 observedAttributes，value 是对应的 props 属性，同名的 DOM property 也映射到该属性；
+/// events 的 props 属性由运行时传入函数，调用时在宿主元素上派发同名的 `CustomEvent`；
+/// slots 对应 shadow root 中的 `<slot>` 和 `<slot name="header">`。
+/// 先检查 `customElements.get` 是为了 HMR 重新执行模块时不会重复注册而报错。
+pub fn gen_define_custom_element(tag: Atom, component: Ident, usage: PropsUsage) -> ModuleItem {
+  let custom_elements = || ast_create_expr_ident(global_ident(JINGE_CUSTOM_ELEMENTS.clone()));
+  let registry_call = |method: &str, args: Vec<Box<Expr>>| {
+    ast_create_expr_call(
+      ast_create_expr_member(
+        custom_elements(),
+        MemberProp::Ident(IdentName::from(method)),
+      ),
+      args.into_iter().map(ast_create_arg_expr).collect(),
+    )
+  };
+
+  let attrs = usage
+    .attrs
+    .into_iter()
+    .map(|prop| {
+      (
+        PropName::Str(Str::from(to_kebab_case(&prop))),
+        ast_create_expr_lit_str(prop),
+      )
+    })
+    .collect();
+  let events = usage
+    .events
+    .into_iter()
+    .map(|prop| {
+      let event = to_kebab_case(&prop[2..]);
+      (
+        PropName::Ident(IdentName::from(prop)),
+        ast_create_expr_lit_str(event.into()),
+      )
+    })
+    .collect();
+  let slots = usage
+    .default_slot
+    .then(|| ast_create_expr_ident(JINGE_IMPORT_DEFAULT_SLOT.local()))
+    .into_iter()
+    .chain(usage.slots.into_iter().map(ast_create_expr_lit_str))
+    .map(|slot| Some(ast_create_arg_expr(slot)))
+    .collect();
+  let meta = tpl_prop_obj(vec![
+    (PropName::Ident("attrs".into()), tpl_prop_obj(attrs)),
+    (PropName::Ident("events".into()), tpl_prop_obj(events)),
+    (
+      PropName::Ident("slots".into()),
+      Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: slots,
+      })),
+    ),
+  ]);
+
+  let define = registry_call(
+    "define",
+    vec![
+      ast_create_expr_lit_str(tag.clone()),
+      ast_create_expr_call(
+        ast_create_expr_ident(JINGE_IMPORT_CREATE_CUSTOM_ELEMENT.local()),
+        vec![
+          ast_create_arg_expr(ast_create_expr_ident(component)),
+          ast_create_arg_expr(meta),
+        ],
+      ),
+    ],
+  );
+  ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+    span: DUMMY_SP,
+    expr: Box::new(Expr::Bin(BinExpr {
+      span: DUMMY_SP,
+      op: BinaryOp::LogicalOr,
+      left: registry_call("get", vec![ast_create_expr_lit_str(tag)]),
+      right: define,
+    })),
+  }))
+}
+
+/// `maxCount` 转换为 `max-count`，`ItemSelect` 转换为 `item-select`。
+fn to_kebab_case(name: &str) -> String {
+  let mut out = String::with_capacity(name.len() + 4);
+  name.chars().enumerate().for_each(|(i, c)| {
+    if c.is_ascii_uppercase() {
+      if i > 0 {
+        out.push('-');
+      }
+      out.push(c.to_ascii_lowercase());
+    } else {
+      out.push(c);
+    }
+  });
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{inner_transform, TransformOutput};
+
+  #[test]
+  fn test_custom_element() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "// jinge-custom-element x-card
+export function Card({ maxCount, onItemSelect, children }) {
+  return <div onClick={() => onItemSelect(maxCount)}>{children.header}{children}</div>;
+}
+// jinge-custom-element card
+export const A = (props) => <p />;
+// jinge-custom-element x-b
+export const b = 1;"
+        .into(),
+      Default::default(),
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .filter_map(|d| d.code.as_deref())
+      .collect();
+    assert_eq!(codes, ["JG1004", "JG1005"]);
+    assert!(code.contains("createCustomElement as createCustomElement$jg$"));
+    assert!(code.contains(r#"customElements.get("x-card") || customElements.define("x-card", createCustomElement$jg$(Card, {"#));
+    assert!(code.contains(r#""max-count": "maxCount""#));
+    assert!(code.contains(r#"onItemSelect: "item-select""#));
+    assert!(code.contains("DEFAULT_SLOT$jg$,\n        \"header\""));
+    assert!(!code.contains(r#"customElements.define("card""#));
+
+    // 模块中同名的局部变量不会遮蔽全局的 customElements
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "const customElements = 1;
+// jinge-custom-element x-card
+export function Card(props) {
+  return <div>{props.a}{customElements}</div>;
+}"
+      .into(),
+      Default::default(),
+    );
+    assert!(code.contains("const customElements1 = 1;"));
+    assert!(code.contains(r#"customElements.get("x-card") || customElements.define("x-card""#));
+  }
+}
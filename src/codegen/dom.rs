@@ -0,0 +1,847 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use swc_core::atoms::Atom;
+use swc_core::common::{Span, Spanned, DUMMY_SP};
+use swc_core::ecma::ast::*;
+
+use crate::ast::*;
+use crate::common::*;
+use crate::parser::ir::*;
+use crate::parser::Parent;
+
+use super::tpl::*;
+
+/// 一组节点生成的渲染代码：按顺序执行的渲染语句，以及最终的节点数组。
+pub(super) struct Block {
+  parent: Parent,
+  root_container: bool,
+  /// hydrate 时遍历当前父节点下已有 DOM 节点的游标，参看 [DomCodegen::cursor]
+  cursor: Option<Ident>,
+  pub(super) stmts: Vec<Stmt>,
+  pub(super) expressions: Vec<ExprOrSpread>,
+}
+impl Block {
+  fn new(parent: Parent, root_container: bool, cursor: Option<Ident>) -> Self {
+    Self {
+      parent,
+      root_container,
+      cursor,
+      stmts: vec![],
+      expressions: vec![],
+    }
+  }
+  /// 生成 slot 的渲染函数体：`{ ...stmts; return [...expressions] }`，没有渲染语句时直接返回数组。
+  fn into_body(self) -> Box<BlockStmtOrExpr> {
+    let arr = Box::new(Expr::Array(ArrayLit {
+      span: DUMMY_SP,
+      elems: self.expressions.into_iter().map(Some).collect(),
+    }));
+    if self.stmts.is_empty() {
+      return Box::new(BlockStmtOrExpr::Expr(arr));
+    }
+    let mut stmts = self.stmts;
+    stmts.push(Stmt::Return(ReturnStmt {
+      span: DUMMY_SP,
+      arg: Some(arr),
+    }));
+    Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+      span: DUMMY_SP,
+      ctxt: Default::default(),
+      stmts,
+    }))
+  }
+}
+
+/// 没有副作用的表达式可以直接放在节点数组或者 createEle 的参数中，不需要先赋值给局部变量。
+/// 不包含动态内容的 html 元素（参数都是常量或局部变量的 createEle 调用）也认为没有副作用。
+pub fn is_pure(expr: &Expr) -> bool {
+  match expr {
+    Expr::Lit(_) | Expr::Ident(_) => true,
+    Expr::Object(obj) => obj.props.iter().all(|p| {
+      matches!(p, PropOrSpread::Prop(p) if matches!(p.as_ref(), Prop::KeyValue(kv) if is_pure(&kv.value)))
+    }),
+    Expr::Call(CallExpr {
+      callee: Callee::Expr(callee),
+      args,
+      ..
+    }) => {
+      let Expr::Ident(id) = callee.as_ref() else {
+        return false;
+      };
+      let id = id.to_id();
+      let create_ele: [&JingeImport; 6] = [
+        &JINGE_IMPORT_CREATE_ELE,
+        &JINGE_IMPORT_CREATE_ELE_A,
+        &JINGE_IMPORT_CREATE_SVG_ELE,
+        &JINGE_IMPORT_CREATE_SVG_ELE_A,
+        &JINGE_IMPORT_CREATE_MATH_ELE,
+        &JINGE_IMPORT_CREATE_MATH_ELE_A,
+      ];
+      create_ele.iter().any(|i| id == i.local().to_id())
+        && args.iter().all(|a| a.spread.is_none() && is_pure(&a.expr))
+    }
+    _ => false,
+  }
+}
+
+type SplitValues<K> = (Vec<(K, Box<Expr>)>, Vec<(K, ExprParseResult)>);
+
+/// 把属性拆分为常量和需要监听的表达式两部分，各自保持书写顺序。
+pub(super) fn split_values<K>(values: Vec<(K, Value)>) -> SplitValues<K> {
+  let mut consts = vec![];
+  let mut watches = vec![];
+  values.into_iter().for_each(|(k, v)| match v {
+    Value::Const(e) => consts.push((k, e)),
+    Value::Bind { watch, .. } => watches.push((k, watch)),
+  });
+  (consts, watches)
+}
+
+/// 浏览器 DOM 渲染的代码生成：把模板 IR 生成为平铺的渲染语句和最终返回的节点数组。
+pub struct DomCodegen {
+  block: Block,
+  /// 模块中已经提升的静态模板数量，同一个模块中的所有组件共享，参看 hoist.rs
+  pub(super) hoisted_templates: Rc<Cell<usize>>,
+  /// 已经生成的局部变量数量，参看 [DomCodegen::new_local]
+  locals: usize,
+  /// 是否生成 hydrate 代码，参看 [DomCodegen::hydrate]
+  hydrate: bool,
+  /// 开发模式下 hydrate 代码中标注的源码位置
+  locator: Option<SourceLocator>,
+}
+
+impl DomCodegen {
+  pub fn new(hoisted_templates: Rc<Cell<usize>>) -> Self {
+    Self {
+      block: Block::new(Parent::Component, true, None),
+      hoisted_templates,
+      locals: 0,
+      hydrate: false,
+      locator: None,
+    }
+  }
+  /// 生成接管服务端渲染出的 DOM 的代码：html 元素和文本节点不再创建，而是通过游标按文档顺序从已有的 DOM 中查找：
+  /// ```js
+  /// const cur$jg$0 = hydrateCursor$jg$(this);
+  /// const $jg$1 = claimEle$jg$(cur$jg$0, "div", "src/a.tsx:3:10");
+  /// const cur$jg$2 = childCursor$jg$($jg$1);
+  /// const $jg$3 = claimText$jg$(cur$jg$2);
+  /// watchForRender$jg$(...);
+  /// addEvent$jg$($jg$1, "click", ...);
+  /// ```
+  /// 属性已经在服务端渲染时输出，只需要监听变更；事件、ref 和组件的渲染和 Dom 模式一样。
+  /// 组件和 Slot 通过 `hydrateFunctionComponent`/`hydrateSlotFunction` 把游标传递给子组件继续查找。
+  /// locator 不为空时（开发模式），查找节点时带上模板的源码位置，运行时发现不匹配时报告出错的位置。
+  pub fn hydrate(mut self, locator: Option<SourceLocator>) -> Self {
+    self.hydrate = true;
+    self.locator = locator;
+    self
+  }
+  /// 生成渲染语句和最终返回的节点数组。
+  pub fn gen(mut self, nodes: Vec<Node>) -> (Vec<Stmt>, Box<Expr>) {
+    nodes.into_iter().for_each(|n| self.gen_node(n));
+    (
+      self.block.stmts,
+      Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: self.block.expressions.into_iter().map(Some).collect(),
+      })),
+    )
+  }
+  /// 生成父节点下的子节点，返回子节点的渲染代码。
+  pub(super) fn gen_children(
+    &mut self,
+    nodes: Vec<Node>,
+    parent: Parent,
+    root_container: bool,
+  ) -> Block {
+    self.gen_children_with_cursor(nodes, parent, root_container, None)
+  }
+  fn gen_children_with_cursor(
+    &mut self,
+    nodes: Vec<Node>,
+    parent: Parent,
+    root_container: bool,
+    cursor: Option<Ident>,
+  ) -> Block {
+    let current = std::mem::replace(&mut self.block, Block::new(parent, root_container, cursor));
+    nodes.into_iter().for_each(|n| self.gen_node(n));
+    std::mem::replace(&mut self.block, current)
+  }
+  /// hydrate 时当前父节点的游标。父亲是组件（或 Slot）时，第一次使用时从容器获取：`const cur$jg$0 = hydrateCursor$jg$(this)`。
+  fn cursor(&mut self) -> Ident {
+    if let Some(c) = &self.block.cursor {
+      return c.clone();
+    }
+    let c = self.new_local(JINGE_CURSOR_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(
+      c.clone(),
+      ast_create_expr_call(
+        ast_create_expr_ident(JINGE_IMPORT_HYDRATE_CURSOR.local()),
+        vec![ast_create_arg_expr(ast_create_id_of_container(
+          self.root_container(),
+        ))],
+      ),
+    ));
+    self.block.cursor = Some(c.clone());
+    c
+  }
+  /// `claimEle$jg$(cur$jg$0, "div", "src/a.tsx:3:10")` 或 `claimText$jg$(cur$jg$0)`，开发模式下带上源码位置。
+  fn claim(&mut self, import: &JingeImport, tag: Option<Atom>, sp: Span) -> Box<Expr> {
+    let mut args = vec![ast_create_arg_expr(ast_create_expr_ident(self.cursor()))];
+    if let Some(tag) = tag {
+      args.push(ast_create_arg_expr(ast_create_expr_lit_str(tag)));
+    }
+    if let Some(loc) = self.locator.as_ref().and_then(|l| l.locate(sp)) {
+      args.push(ast_create_arg_expr(ast_create_expr_lit_str(loc)));
+    }
+    ast_create_expr_call(ast_create_expr_ident(import.local()), args)
+  }
+  #[inline]
+  pub(super) fn is_parent_component(&self) -> bool {
+    matches!(self.block.parent, Parent::Component)
+  }
+  #[inline]
+  pub(super) fn root_container(&self) -> bool {
+    self.block.root_container
+  }
+  /// 生成渲染代码中唯一的局部变量，比如 `$jg$3`、`attrs$jg$4`。
+  /// 渲染代码是平铺的语句序列（而不是嵌套的立即执行函数），因此同一个模板中的局部变量不能重名。
+  pub(super) fn new_local(&mut self, prefix: &str) -> Ident {
+    let id = private_ident(format!("{}{}", prefix, self.locals).into());
+    self.locals += 1;
+    id
+  }
+  #[inline]
+  /// 将渲染语句添加到当前的 Block 中。
+  pub(super) fn push_stmt(&mut self, stmt: Stmt) {
+    self.block.stmts.push(stmt);
+  }
+  #[inline]
+  pub(super) fn push_expr_stmt(&mut self, expr: Box<Expr>) {
+    self.push_stmt(Stmt::Expr(ExprStmt {
+      span: DUMMY_SP,
+      expr,
+    }));
+  }
+  /// 有副作用的表达式（比如渲染组件、创建文本节点并 push 到 ROOT_NODES）先赋值给局部变量，
+  /// 这样所有的副作用都按模板中的顺序在渲染语句中执行，和节点在数组中的顺序一致。
+  fn bind_local(&mut self, e: Box<Expr>) -> Box<Expr> {
+    if is_pure(&e) {
+      return e;
+    }
+    let id = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(id.clone(), e));
+    ast_create_expr_ident(id)
+  }
+  /// hydrate 时 html 元素的子节点已经在 DOM 中，不需要节点数组，有副作用的表达式（比如渲染组件）直接作为渲染语句执行。
+  fn skip_expression(&mut self, e: Box<Expr>) -> Option<Box<Expr>> {
+    if !self.hydrate || self.is_parent_component() {
+      return Some(e);
+    }
+    if !is_pure(&e) {
+      self.push_expr_stmt(e);
+    }
+    None
+  }
+  #[inline]
+  /// push expression to current block
+  pub(super) fn push_expression(&mut self, e: Box<Expr>) {
+    let Some(e) = self.skip_expression(e) else {
+      return;
+    };
+    let e = self.bind_local(e);
+    self.block.expressions.push(ast_create_arg_expr(e));
+  }
+  #[inline]
+  /// push spread expression to current block
+  fn push_expression_with_spread(&mut self, e: Box<Expr>) {
+    let Some(e) = self.skip_expression(e) else {
+      return;
+    };
+    let e = self.bind_local(e);
+    self.block.expressions.push(ExprOrSpread {
+      spread: Some(DUMMY_SP),
+      expr: e,
+    });
+  }
+
+  fn gen_node(&mut self, node: Node) {
+    match node {
+      Node::Element(el) if self.hydrate => self.gen_hydrate_element(el),
+      Node::Element(el) => {
+        if let Err(el) = self.gen_hoisted_element(el) {
+          self.gen_element(*el);
+        }
+      }
+      Node::Component(c) => self.gen_component(c),
+      Node::Dynamic(Dynamic {
+        component,
+        mut attrs,
+        ref_prop,
+        slots,
+      }) => {
+        // component 作为运行时 Dynamic 组件的第一个属性，其余属性、事件和 Slot 由 Dynamic 转交给实际渲染的组件或元素。
+        attrs.insert(
+          0,
+          (IdentName::from(JINGE_COMPONENT_ATTR.clone()), component),
+        );
+        self.gen_component(Component {
+          tag: ast_create_expr_ident(JINGE_IMPORT_DYNAMIC.local()),
+          attrs,
+          spread: None,
+          ref_prop,
+          slots,
+        })
+      }
+      Node::Text(text) if self.hydrate => self.gen_hydrate_text(text.span()),
+      Node::Text(text) => self.push_expression(tpl_render_const_text(
+        text,
+        self.is_parent_component(),
+        self.root_container(),
+      )),
+      Node::TextBind { watch, value, expr } => self.gen_text_bind(watch, value, expr.span()),
+      Node::RenderSlot(s) => self.gen_render_slot(s),
+      Node::If(If { expect, slots }) => self.gen_component(Component {
+        tag: ast_create_expr_ident(JINGE_IMPORT_IF.local()),
+        attrs: vec![(IdentName::from(JINGE_EXPECT.clone()), expect)],
+        spread: None,
+        ref_prop: None,
+        slots,
+      }),
+      Node::For(For {
+        looop,
+        key_fn,
+        slots,
+      }) => {
+        let mut attrs = vec![(IdentName::from(JINGE_LOOP.clone()), looop)];
+        if let Some(key_fn) = key_fn {
+          attrs.push((IdentName::from(JINGE_KEY_FN.clone()), key_fn));
+        }
+        self.gen_component(Component {
+          tag: ast_create_expr_ident(JINGE_IMPORT_FOR.local()),
+          attrs,
+          spread: None,
+          ref_prop: None,
+          slots,
+        })
+      }
+      Node::Intl(intl) => self.gen_intl(intl),
+      Node::Error(code) => self.push_expression(tpl_error_stub(code)),
+    }
+  }
+
+  /// 创建文本节点并监听表达式的变更，参看 [tpl_render_expr_text]。
+  fn gen_text_bind(&mut self, watch: ExprParseResult, value: Box<Expr>, sp: Span) {
+    let node = if self.hydrate {
+      self.claim(&JINGE_IMPORT_CLAIM_TEXT, None, sp)
+    } else {
+      ast_create_expr_call(
+        ast_create_expr_ident(JINGE_IMPORT_CREATE_TEXT_NODE.local()),
+        vec![ast_create_arg_expr(ast_create_expr_lit_str(
+          JINGE_EMPTY_STR.clone(),
+        ))],
+      )
+    };
+    let el = self.new_local(JINGE_EL_PREFIX);
+    tpl_render_expr_text(
+      el.clone(),
+      node,
+      watch,
+      value,
+      self.is_parent_component(),
+      self.root_container(),
+    )
+    .into_iter()
+    .for_each(|stmt| self.push_stmt(stmt));
+    self.push_expression(ast_create_expr_ident(el));
+  }
+
+  /// hydrate 时查找已有的文本节点。父亲是组件时，文本节点需要放入组件的根节点数组。
+  fn gen_hydrate_text(&mut self, sp: Span) {
+    let claim = self.claim(&JINGE_IMPORT_CLAIM_TEXT, None, sp);
+    if !self.is_parent_component() {
+      self.push_expr_stmt(claim);
+      return;
+    }
+    let el = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(el.clone(), claim));
+    self.push_expr_stmt(tpl_push_el_code(true, self.root_container(), el.clone()));
+    self.push_expression(ast_create_expr_ident(el));
+  }
+
+  /// hydrate 时查找已有的 html 元素，再用元素的游标查找子节点，参看 [DomCodegen::hydrate]。
+  fn gen_hydrate_element(&mut self, el: Element) {
+    let claim = self.claim(
+      &JINGE_IMPORT_CLAIM_ELE,
+      Some(el.tag.sym.clone()),
+      el.tag.span,
+    );
+    let id = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(id.clone(), claim));
+    let root_container = self.root_container();
+    if !el.children.is_empty() {
+      let cursor = self.new_local(JINGE_CURSOR_PREFIX);
+      self.push_stmt(ast_create_stmt_decl_const(
+        cursor.clone(),
+        ast_create_expr_call(
+          ast_create_expr_ident(JINGE_IMPORT_CHILD_CURSOR.local()),
+          vec![ast_create_arg_expr(ast_create_expr_ident(id.clone()))],
+        ),
+      ));
+      let parent = el.children_parent();
+      let children =
+        self.gen_children_with_cursor(el.children, parent, root_container, Some(cursor));
+      self.block.stmts.extend(children.stmts);
+    }
+    let (_, watch_props) = split_values(el.attrs);
+    let (_, watch_style) = split_values(el.style);
+    let (_, watch_classes) = split_values(el.class_toggles);
+    el.events
+      .into_iter()
+      .for_each(|evt| self.push_expr_stmt(tpl_add_event(id.clone(), evt)));
+    watch_props.into_iter().for_each(|(attr_name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_set_html_attr(
+        attr_name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    watch_style.into_iter().for_each(|(name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_set_style(
+        name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    watch_classes.into_iter().for_each(|(name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_toggle_class(
+        name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    if let Some(watch_expr) = el.select_values {
+      self.push_expr_stmt(tpl_watch_and_set_select_values(
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    }
+    if let Some(r) = el.ref_prop {
+      self.push_expr_stmt(tpl_set_ref_code(r, id.clone()));
+    }
+    if self.is_parent_component() {
+      self.push_expr_stmt(tpl_push_el_code(true, root_container, id.clone()));
+    }
+    self.push_expression(ast_create_expr_ident(id));
+  }
+
+  fn gen_element(&mut self, el: Element) {
+    let (const_props, watch_props) = split_values(el.attrs);
+    // `xlink:href` 等命名空间属性不能放在创建元素的属性对象中，需要在元素创建后使用 setAttributeNS 设置。
+    let (ns_props, const_props): (Vec<_>, Vec<_>) = const_props
+      .into_iter()
+      .partition(|(name, _)| attr_namespace(&name.sym).is_some());
+    let (const_style, watch_style) = split_values(el.style);
+    let (const_classes, watch_classes) = split_values(el.class_toggles);
+    let callee_ident = tpl_create_ele_callee(el.ns, !const_props.is_empty());
+    let parent = el.ns.children_parent(&el.tag.sym);
+    let mut args = vec![ast_create_arg_expr(ast_create_expr_lit_str(
+      el.tag.sym.clone(),
+    ))];
+    if !const_props.is_empty() {
+      // 属性表达式需要在子节点之前求值，和 jsx 中的书写顺序保持一致。
+      let props = tpl_lit_obj(const_props);
+      let props = if is_pure(&props) {
+        props
+      } else {
+        let id = self.new_local(JINGE_ATTR_PREFIX);
+        self.push_stmt(ast_create_stmt_decl_const(id.clone(), props));
+        ast_create_expr_ident(id)
+      };
+      args.push(ast_create_arg_expr(props));
+    }
+    let root_container = self.root_container();
+    let children = self.gen_children(el.children, parent, root_container);
+    // 子节点的渲染语句需要在当前元素创建之前执行。
+    self.block.stmts.extend(children.stmts);
+    args.extend(children.expressions);
+    let output = ast_create_expr_call(ast_create_expr_ident(callee_ident), args);
+
+    let is_parent_component = self.is_parent_component();
+    if el.ref_prop.is_none()
+      && !is_parent_component
+      && el.events.is_empty()
+      && watch_props.is_empty()
+      && ns_props.is_empty()
+      && const_style.is_empty()
+      && watch_style.is_empty()
+      && const_classes.is_empty()
+      && watch_classes.is_empty()
+      && el.select_values.is_none()
+    {
+      self.push_expression(output);
+      return;
+    }
+
+    let id = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(id.clone(), output));
+    ns_props.into_iter().for_each(|(attr_name, value)| {
+      self.push_expr_stmt(tpl_set_html_attr(id.clone(), attr_name.sym, value));
+    });
+    const_style.into_iter().for_each(|(name, value)| {
+      self.push_expr_stmt(tpl_set_style(id.clone(), name, value));
+    });
+    const_classes.into_iter().for_each(|(name, value)| {
+      self.push_expr_stmt(tpl_toggle_class(id.clone(), name, value));
+    });
+    el.events
+      .into_iter()
+      .for_each(|evt| self.push_expr_stmt(tpl_add_event(id.clone(), evt)));
+    watch_props.into_iter().for_each(|(attr_name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_set_html_attr(
+        attr_name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    watch_style.into_iter().for_each(|(name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_set_style(
+        name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    watch_classes.into_iter().for_each(|(name, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_toggle_class(
+        name,
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    });
+    if let Some(watch_expr) = el.select_values {
+      self.push_expr_stmt(tpl_watch_and_set_select_values(
+        watch_expr,
+        root_container,
+        id.clone(),
+      ));
+    }
+    if let Some(r) = el.ref_prop {
+      self.push_expr_stmt(tpl_set_ref_code(r, id.clone()));
+    }
+    if is_parent_component {
+      self.push_expr_stmt(tpl_push_el_code(true, root_container, id.clone()));
+    }
+    // 当前 html 元素添加到父亲的节点数组中。父亲是组件时，节点数组是组件的某个 Slot 的渲染结果。
+    self.push_expression(ast_create_expr_ident(id));
+  }
+
+  fn gen_component(&mut self, c: Component) {
+    let (const_props, watch_props) = split_values(c.attrs);
+    let root_container = self.root_container();
+
+    // 属性需要在子节点（slot 函数）之前求值，和 jsx 中的书写顺序保持一致。
+    let attrs_id = if !const_props.is_empty() || !watch_props.is_empty() {
+      let id = self.new_local(JINGE_ATTR_PREFIX);
+      self.push_stmt(ast_create_stmt_decl_const(
+        id.clone(),
+        if !watch_props.is_empty() {
+          ast_create_expr_call(
+            ast_create_expr_ident(JINGE_IMPORT_VM.local()),
+            vec![ast_create_arg_expr(tpl_lit_obj(const_props))],
+          )
+        } else {
+          tpl_lit_obj(const_props)
+        },
+      ));
+      watch_props
+        .into_iter()
+        .for_each(|(attr_name, expr_result)| {
+          self.push_expr_stmt(tpl_watch_and_set_component_attr(
+            attr_name,
+            expr_result,
+            root_container,
+            id.clone(),
+          ));
+        });
+      Some(id)
+    } else {
+      None
+    };
+
+    let mut slots = c.slots;
+    let mut args = vec![ast_create_arg_expr(ast_create_expr_member(
+      ast_create_id_of_container(root_container),
+      MemberProp::Computed(ComputedPropName {
+        span: DUMMY_SP,
+        expr: ast_create_expr_ident(JINGE_IMPORT_CONTEXT.local()),
+      }),
+    ))];
+    let has_named_slots = slots.len() > 1;
+    if has_named_slots {
+      assert!(slots[0].children.is_empty());
+      let x: Vec<_> = slots
+        .into_iter()
+        .skip(1)
+        .filter(|s| !s.children.is_empty()) // 跳过默认 DEFAULT_SLOT，一定是空的
+        .map(|s| {
+          let (name, params) = (s.name.clone(), s.params);
+          let body = self.gen_children(s.children, Parent::Component, false);
+          (IdentName::from(name), self.gen_slot_fn(params, body))
+        })
+        .collect();
+      args.push(ast_create_arg_expr(tpl_lit_obj(x)));
+    } else {
+      let default_slot = slots.pop().unwrap();
+      if !default_slot.children.is_empty() {
+        let body = self.gen_children(default_slot.children, Parent::Component, false);
+        args.push(ast_create_arg_expr(
+          self.gen_slot_fn(default_slot.params, body),
+        ))
+      }
+    }
+
+    let el = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(
+      el.clone(),
+      ast_create_expr_call(
+        ast_create_expr_ident(if has_named_slots {
+          JINGE_IMPORT_NEW_COM_SLOTS.local()
+        } else {
+          JINGE_IMPORT_NEW_COM_DEFAULT_SLOT.local()
+        }),
+        args,
+      ),
+    ));
+    self.push_expr_stmt(tpl_push_el_code(
+      self.is_parent_component(),
+      root_container,
+      el.clone(),
+    ));
+    if let Some(r) = c.ref_prop {
+      self.push_expr_stmt(tpl_set_ref_code(r, el.clone()));
+    }
+
+    let mut render_fc_args = vec![
+      ast_create_arg_expr(ast_create_expr_ident(el)),
+      ast_create_arg_expr(c.tag),
+    ];
+    if let Some(id) = attrs_id.or(c.spread) {
+      render_fc_args.push(ast_create_arg_expr(ast_create_expr_ident(id)));
+    }
+    let render_fc = self.hydrate_or(
+      &JINGE_IMPORT_RENDER_FC,
+      &JINGE_IMPORT_HYDRATE_FC,
+      render_fc_args,
+    );
+    self.push_expression_with_spread(render_fc);
+  }
+
+  /// 调用渲染组件或 Slot 的运行时函数。hydrate 时使用对应的 hydrate 版本，并把当前的游标作为第一个参数传递。
+  fn hydrate_or(
+    &mut self,
+    render: &JingeImport,
+    hydrate: &JingeImport,
+    mut args: Vec<ExprOrSpread>,
+  ) -> Box<Expr> {
+    if !self.hydrate {
+      return ast_create_expr_call(ast_create_expr_ident(render.local()), args);
+    }
+    args.insert(0, ast_create_arg_expr(ast_create_expr_ident(self.cursor())));
+    ast_create_expr_call(ast_create_expr_ident(hydrate.local()), args)
+  }
+
+  /// Slot 函数：`(host, ...params) => { ...stmts; return [...] }`
+  fn gen_slot_fn(&self, mut params: Vec<Pat>, body: Block) -> Box<Expr> {
+    let mut slot_params = vec![Pat::Ident(BindingIdent::from(JINGE_HOST_IDENT.ident()))];
+    slot_params.append(&mut params);
+    ast_create_expr_arrow_fn(slot_params, body.into_body())
+  }
+
+  /// 生成常量和监听属性组成的 vm 局部变量，返回局部变量。没有属性时返回 None。
+  fn gen_vm_props(&mut self, props: Vec<(PropName, Value)>) -> Option<Ident> {
+    if props.is_empty() {
+      return None;
+    }
+    let (const_props, watch_props) = split_values(props);
+    let obj = tpl_prop_obj(const_props);
+    let vm = self.new_local(JINGE_ATTR_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(
+      vm.clone(),
+      if watch_props.is_empty() {
+        obj
+      } else {
+        ast_create_expr_call(
+          ast_create_expr_ident(JINGE_IMPORT_VM.local()),
+          vec![ast_create_arg_expr(obj)],
+        )
+      },
+    ));
+    let root_container = self.root_container();
+    watch_props.into_iter().for_each(|(prop, watch_expr)| {
+      self.push_expr_stmt(tpl_watch_and_set_vm_prop(
+        prop,
+        watch_expr,
+        root_container,
+        vm.clone(),
+      ));
+    });
+    Some(vm)
+  }
+
+  fn gen_render_slot(&mut self, s: RenderSlot) {
+    let slot_vm_id = self.gen_vm_props(s.args).or(s.spread);
+    let root_container = self.root_container();
+
+    let el = self.new_local(JINGE_EL_PREFIX);
+    self.push_stmt(ast_create_stmt_decl_const(
+      el.clone(),
+      ast_create_expr_call(
+        ast_create_expr_ident(JINGE_IMPORT_NEW_COM_DEFAULT_SLOT.local()),
+        vec![ast_create_arg_expr(ast_create_expr_member(
+          ast_create_id_of_container(root_container),
+          MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: ast_create_expr_ident(JINGE_IMPORT_CONTEXT.local()),
+          }),
+        ))],
+      ),
+    ));
+    self.push_expr_stmt(tpl_push_el_code(
+      self.is_parent_component(),
+      root_container,
+      el.clone(),
+    ));
+
+    let mut args = vec![
+      ast_create_arg_expr(ast_create_expr_ident(el)),
+      ast_create_arg_expr(ast_create_expr_member(
+        ast_create_expr_member(
+          ast_create_expr_this(),
+          MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: ast_create_expr_ident(JINGE_IMPORT_SLOTS.local()),
+          }),
+        ),
+        if let Some(slot_name) = s.name {
+          MemberProp::Ident(IdentName::from(slot_name))
+        } else {
+          MemberProp::Computed(ComputedPropName {
+            span: DUMMY_SP,
+            expr: ast_create_expr_ident(JINGE_IMPORT_DEFAULT_SLOT.local()),
+          })
+        },
+      )),
+    ];
+    if let Some(id) = slot_vm_id {
+      args.push(ast_create_arg_expr(ast_create_expr_ident(id)));
+    }
+    let render_slot = self.hydrate_or(&JINGE_IMPORT_RENDER_SLOT, &JINGE_IMPORT_HYDRATE_SLOT, args);
+    self.push_expression_with_spread(render_slot);
+  }
+
+  fn gen_intl(&mut self, intl: Intl) {
+    let has_watch = intl
+      .params
+      .iter()
+      .any(|(_, v)| matches!(v, Value::Bind { .. }));
+    let params = if has_watch {
+      self
+        .gen_vm_props(intl.params)
+        .map(|vm| ast_create_arg_expr(ast_create_expr_ident(vm)))
+    } else if intl.params.is_empty() {
+      None
+    } else {
+      let (const_props, _) = split_values(intl.params);
+      Some(ast_create_arg_expr(tpl_prop_obj(const_props)))
+    };
+    let cursor = self.hydrate.then(|| self.cursor());
+    let expr = tpl_render_intl_text(
+      cursor,
+      intl.is_rich_text,
+      intl.key,
+      params,
+      intl.default_text.as_ref(),
+      self.is_parent_component(),
+      self.root_container(),
+    );
+    if intl.is_rich_text {
+      self.push_expression_with_spread(expr);
+    } else {
+      self.push_expression(expr);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::common::RenderMode;
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_flat_render() {
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  if (props.a) return <p>{props.a}</p>;
+  return <>{'x'}<div onClick={() => go()}><Item a={props.b} /></div></>;
+}"
+      .into(),
+      TransformOptions::default(),
+    );
+    assert!(!code.contains(")()"));
+    // 提前 return 的渲染语句放在语句块中
+    assert!(code.contains("if (props.a) {\n        const "));
+    // 有副作用的节点按模板中的顺序执行，组件在包含它的 div 之前渲染
+    let text = code.find("textRenderFn$jg$(this, 'x')").unwrap();
+    let item = code.find("renderFunctionComponent$jg$(").unwrap();
+    let div = code.find("createEle$jg$(\"div\", ...").unwrap();
+    let push = code.rfind("this[ROOT_NODES$jg$].push(").unwrap();
+    assert!(text < item && item < div && div < push);
+  }
+
+  #[test]
+  fn test_hydrate() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  return <div onClick={() => {}}>
+    <p>{props.name}</p>
+    <Item a={props.a} />
+  </div>;
+}"
+      .into(),
+      TransformOptions {
+        render_mode: RenderMode::Hydrate,
+        dev: true,
+        ..Default::default()
+      },
+    );
+    assert!(diagnostics.is_empty());
+    assert!(!code.contains("createEle$jg$("));
+    assert!(!code.contains("cloneNode"));
+    // html 元素和文本节点按文档顺序从游标中查找，开发模式下带上源码位置
+    let root = code.find(" = hydrateCursor$jg$(this);").unwrap();
+    let div = code.find(r#", "div", "test.tsx:2:11");"#).unwrap();
+    let children = code.find(" = childCursor$jg$($jg$").unwrap();
+    let text = code.find(r#", "test.tsx:3:9");"#).unwrap();
+    assert!(root < div && div < children && children < text);
+    assert!(code.contains(" = claimText$jg$(cur$jg$"));
+    assert!(code.contains("(v)=>setTextContent$jg$($jg$"));
+    assert!(code.contains("hydrateFunctionComponent$jg$(cur$jg$"));
+    assert!(code.contains(", Item, attrs$jg$"));
+    assert!(code.contains(r#", "click", "#));
+  }
+}
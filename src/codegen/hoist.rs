@@ -0,0 +1,320 @@
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::*;
+
+use crate::ast::*;
+use crate::common::*;
+use crate::parser::ir::{Element, Node};
+use crate::parser::Parent;
+
+use super::dom::{split_values, DomCodegen};
+use super::tpl::{
+  attr_namespace, tpl_add_event, tpl_create_ele_callee, tpl_lit_obj, tpl_push_el_code,
+  tpl_set_html_attr, tpl_set_ref_code, tpl_set_style, tpl_toggle_class,
+  tpl_watch_and_set_html_attr, tpl_watch_and_set_select_values, tpl_watch_and_set_style,
+  tpl_watch_and_toggle_class,
+};
+
+/// 模板中至少包含这么多个 html 元素时，才提升为静态模板。元素太少时 cloneNode 和路径查找的开销并不比直接创建元素更小。
+const HOIST_MIN_ELEMENTS: usize = 3;
+
+/// 分析元素是否可以作为静态模板：子节点只能是文本或者元素，返回模板中的元素数量。
+/// svg、MathML 元素使用对应命名空间的创建函数放入模板，参看 [tpl_create_ele_callee]。
+/// 子节点中包含表达式、组件等动态内容的元素不放入模板，
+/// 而是按常规方式生成后插入到 clone 出来的父元素中的对应位置，参看 [DomCodegen::gen_dynamic_child]。
+fn count_tpl_elements(el: &Element) -> Option<usize> {
+  let mut count = 1;
+  for child in el.children.iter() {
+    match child {
+      Node::Text(t) if matches!(t.as_ref(), Expr::Lit(Lit::Str(_))) => (),
+      Node::Element(c) => count += count_tpl_elements(c).unwrap_or(0),
+      _ => return None,
+    }
+  }
+  Some(count)
+}
+
+/// 第 idx 个静态模板在模块顶部声明的变量，参看 [gen_hoisted_templates_decl]。
+fn tpl_ident(idx: usize) -> Ident {
+  private_ident(format!("tpl$jg${}", idx).into())
+}
+
+/// 生成模块顶部的 `let tpl$jg$0, tpl$jg$1;` 声明。模板在第一次渲染时才创建（而不是模块加载时），
+/// 以免在没有 DOM 的环境中加载模块出错。
+pub fn gen_hoisted_templates_decl(count: usize) -> ModuleItem {
+  ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+    span: DUMMY_SP,
+    ctxt: Default::default(),
+    kind: VarDeclKind::Let,
+    declare: false,
+    decls: (0..count)
+      .map(|idx| VarDeclarator {
+        span: DUMMY_SP,
+        name: Pat::Ident(tpl_ident(idx).into()),
+        init: None,
+        definite: false,
+      })
+      .collect(),
+  }))))
+}
+
+fn stmt(expr: Box<Expr>) -> Stmt {
+  Stmt::Expr(ExprStmt {
+    span: DUMMY_SP,
+    expr,
+  })
+}
+
+fn child_node(parent: Ident, idx: usize) -> Box<Expr> {
+  ast_create_expr_member(
+    ast_create_expr_member(
+      ast_create_expr_ident(parent),
+      MemberProp::Ident(IdentName::from("childNodes")),
+    ),
+    MemberProp::Computed(ComputedPropName {
+      span: DUMMY_SP,
+      expr: Box::new(Expr::Lit(Lit::Num(Number::from(idx)))),
+    }),
+  )
+}
+
+fn call_method(obj: Ident, method: &str, args: Vec<ExprOrSpread>) -> Box<Expr> {
+  ast_create_expr_call(
+    ast_create_expr_member(
+      ast_create_expr_ident(obj),
+      MemberProp::Ident(IdentName::from(method)),
+    ),
+    args,
+  )
+}
+
+impl DomCodegen {
+  /// 将静态结构的元素子树（包括 svg、MathML 子树）提升为模块级的模板，渲染时只需要 `cloneNode(true)`：
+  /// ```js
+  /// let tpl$jg$0;
+  /// // ...
+  /// const $jg$0 = (tpl$jg$0 || (tpl$jg$0 = createEleA$jg$("div", { class: "card" }, createEle$jg$("h1", "Title"), ...))).cloneNode(true);
+  /// const $jg$2 = $jg$0.childNodes[1];
+  /// addEvent$jg$($jg$2, "click", ...);
+  /// ```
+  /// 模板中的动态部分（事件、ref、watch 属性）通过 childNodes 路径找到 clone 出来的元素后设置，
+  /// 包含动态子节点的 html 元素按常规方式生成，再插入到对应的位置。
+  ///
+  /// 不满足提升条件时原样返回 Err，需要按常规方式生成。
+  pub fn gen_hoisted_element(&mut self, el: Element) -> Result<(), Box<Element>> {
+    if !matches!(count_tpl_elements(&el), Some(count) if count >= HOIST_MIN_ELEMENTS) {
+      return Err(Box::new(el));
+    }
+
+    let root = self.new_local(JINGE_EL_PREFIX);
+    let mut stmts = vec![];
+    let build = self.gen_tpl_element(el, root.clone(), &mut stmts);
+
+    let tpl_id = tpl_ident(self.hoisted_templates.get());
+    self.hoisted_templates.set(self.hoisted_templates.get() + 1);
+    let cached = Box::new(Expr::Bin(BinExpr {
+      span: DUMMY_SP,
+      op: BinaryOp::LogicalOr,
+      left: ast_create_expr_ident(tpl_id.clone()),
+      right: Box::new(Expr::Assign(AssignExpr {
+        span: DUMMY_SP,
+        op: AssignOp::Assign,
+        left: AssignTarget::Simple(SimpleAssignTarget::Ident(tpl_id.into())),
+        right: build,
+      })),
+    }));
+    self.push_stmt(ast_create_stmt_decl_const(
+      root.clone(),
+      ast_create_expr_call(
+        ast_create_expr_member(cached, MemberProp::Ident(IdentName::from("cloneNode"))),
+        vec![ast_create_arg_expr(ast_create_expr_lit_bool(true))],
+      ),
+    ));
+    stmts.into_iter().for_each(|s| self.push_stmt(s));
+    if self.is_parent_component() {
+      self.push_expr_stmt(tpl_push_el_code(true, self.root_container(), root.clone()));
+    }
+    self.push_expression(ast_create_expr_ident(root));
+    Ok(())
+  }
+
+  /// 生成模板元素的创建代码，并将 clone 之后需要执行的动态部分代码放入 stmts。el 是 clone 出来的元素对应的局部变量。
+  fn gen_tpl_element(&mut self, tpl: Element, el: Ident, stmts: &mut Vec<Stmt>) -> Box<Expr> {
+    let root_container = self.root_container();
+    let (ns, tag) = (tpl.ns, tpl.tag.sym.clone());
+    let (const_props, watch_props) = split_values(tpl.attrs);
+    let mut static_props = vec![];
+    const_props
+      .into_iter()
+      .for_each(|(name, value)| match value.as_ref() {
+        Expr::Lit(_) if attr_namespace(&name.sym).is_none() => static_props.push((name, value)),
+        _ => stmts.push(stmt(tpl_set_html_attr(el.clone(), name.sym, value))),
+      });
+    let (const_style, watch_style) = split_values(tpl.style);
+    const_style
+      .into_iter()
+      .for_each(|(name, value)| stmts.push(stmt(tpl_set_style(el.clone(), name, value))));
+    let (const_classes, watch_classes) = split_values(tpl.class_toggles);
+    const_classes
+      .into_iter()
+      .for_each(|(name, value)| stmts.push(stmt(tpl_toggle_class(el.clone(), name, value))));
+    tpl
+      .events
+      .into_iter()
+      .for_each(|evt| stmts.push(stmt(tpl_add_event(el.clone(), evt))));
+    watch_props.into_iter().for_each(|(name, watch_expr)| {
+      stmts.push(stmt(tpl_watch_and_set_html_attr(
+        name,
+        watch_expr,
+        root_container,
+        el.clone(),
+      )))
+    });
+    watch_style.into_iter().for_each(|(name, watch_expr)| {
+      stmts.push(stmt(tpl_watch_and_set_style(
+        name,
+        watch_expr,
+        root_container,
+        el.clone(),
+      )))
+    });
+    watch_classes.into_iter().for_each(|(name, watch_expr)| {
+      stmts.push(stmt(tpl_watch_and_toggle_class(
+        name,
+        watch_expr,
+        root_container,
+        el.clone(),
+      )))
+    });
+    if let Some(watch_expr) = tpl.select_values {
+      stmts.push(stmt(tpl_watch_and_set_select_values(
+        watch_expr,
+        root_container,
+        el.clone(),
+      )));
+    }
+    if let Some(r) = tpl.ref_prop {
+      stmts.push(stmt(tpl_set_ref_code(r, el.clone())));
+    }
+
+    let callee = tpl_create_ele_callee(ns, !static_props.is_empty());
+    let mut args = vec![ast_create_arg_expr(ast_create_expr_lit_str(tag.clone()))];
+    if !static_props.is_empty() {
+      args.push(ast_create_arg_expr(tpl_lit_obj(static_props)));
+    }
+    // 动态子元素必须在所有模板子节点的路径查找完成之后再插入，否则会改变 childNodes 的下标。
+    let mut dynamic_children = vec![];
+    let mut idx = 0;
+    tpl.children.into_iter().for_each(|child| match child {
+      Node::Element(c) if count_tpl_elements(&c).is_some() => {
+        let child_el = self.new_local(JINGE_EL_PREFIX);
+        let mut child_stmts = vec![];
+        args.push(ast_create_arg_expr(self.gen_tpl_element(
+          c,
+          child_el.clone(),
+          &mut child_stmts,
+        )));
+        if !child_stmts.is_empty() {
+          stmts.push(ast_create_stmt_decl_const(
+            child_el,
+            child_node(el.clone(), idx),
+          ));
+          stmts.append(&mut child_stmts);
+        }
+        idx += 1;
+      }
+      Node::Element(c) => dynamic_children.push((idx, c)),
+      Node::Text(t) => {
+        args.push(ast_create_arg_expr(t));
+        idx += 1;
+      }
+      _ => unreachable!(),
+    });
+    dynamic_children
+      .into_iter()
+      .enumerate()
+      .for_each(|(k, (static_idx, c))| {
+        let child = self.gen_dynamic_child(c, ns.children_parent(&tag), stmts);
+        // 按顺序插入，此时 childNodes 中 static_idx + k 的位置正好是模板中紧跟在它后面的节点。
+        stmts.push(stmt(if static_idx < idx {
+          call_method(
+            el.clone(),
+            "insertBefore",
+            vec![
+              ast_create_arg_expr(child),
+              ast_create_arg_expr(child_node(el.clone(), static_idx + k)),
+            ],
+          )
+        } else {
+          call_method(el.clone(), "appendChild", vec![ast_create_arg_expr(child)])
+        }));
+      });
+    ast_create_expr_call(ast_create_expr_ident(callee), args)
+  }
+
+  /// 按常规方式生成包含动态子节点的元素，参看 [DomCodegen::gen_element]。生成的渲染语句放入 stmts。
+  /// parent 是模板中父元素的子节点所在的命名空间。
+  fn gen_dynamic_child(&mut self, el: Element, parent: Parent, stmts: &mut Vec<Stmt>) -> Box<Expr> {
+    let root_container = self.root_container();
+    let mut block = self.gen_children(vec![Node::Element(el)], parent, root_container);
+    stmts.append(&mut block.stmts);
+    block.expressions.pop().unwrap().expr
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_hoist_static_template() {
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  return <div class=\"card\">
+    <h2>{props.title}</h2>
+    <ul><li onClick={() => go()}>a</li><li>b</li></ul>
+  </div>;
+}
+export function B() { return <p><b>x</b></p>; }"
+        .into(),
+      TransformOptions::default(),
+    );
+    assert!(code.contains("let tpl$jg$0;"));
+    assert!(!code.contains("tpl$jg$1"));
+    assert!(code.contains(".cloneNode(true)"));
+    assert!(code.contains("createEle$jg$(\"li\", \"b\")"));
+    // li 通过 childNodes 路径找到后绑定事件，h2 包含动态文本，按常规方式生成后插入到模板中的位置
+    let li = code.find(".childNodes[0];").unwrap();
+    let evt = code.find("addEvent$jg$(").unwrap();
+    let h2 = code.find(".insertBefore(createEle$jg$(\"h2\", ").unwrap();
+    assert!(li < evt && evt < h2);
+  }
+
+  #[test]
+  fn test_hoist_svg_template() {
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  return <div><svg><path d=\"M0\"/><path xlink:href=\"#a\"/></svg><svg><g><text>{props.t}</text></g></svg></div>;
+}
+export function B() { return <svg><foreignObject><p>{1}</p></foreignObject><rect/></svg>; }"
+        .into(),
+      TransformOptions::default(),
+    );
+    assert!(code.contains("let tpl$jg$0, tpl$jg$1;"));
+    // svg 子树使用 svg 命名空间的创建函数放入模板
+    assert!(code.contains(
+      "createSvgEle$jg$(\"svg\", createSvgEleA$jg$(\"path\", {\n        \"d\": \"M0\"\n    }), createSvgEle$jg$(\"path\"))"
+    ));
+    assert!(code.contains(", \"http://www.w3.org/1999/xlink\", \"xlink:href\", \"#a\");"));
+    // 包含动态子节点的 svg 元素仍然使用 svg 命名空间创建
+    assert!(code.contains(".appendChild(createSvgEle$jg$(\"text\", "));
+    // foreignObject 中的子节点回到 html 命名空间
+    assert!(code.contains(
+      "createSvgEle$jg$(\"svg\", createSvgEle$jg$(\"foreignObject\"), createSvgEle$jg$(\"rect\"))"
+    ));
+    assert!(code.contains(".appendChild(createEle$jg$(\"p\", "));
+  }
+}
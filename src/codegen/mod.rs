@@ -0,0 +1,10 @@
+pub use custom_element::gen_define_custom_element;
+pub use dom::DomCodegen;
+pub use hoist::gen_hoisted_templates_decl;
+pub use ssr::SsrCodegen;
+
+mod custom_element;
+mod dom;
+mod hoist;
+mod ssr;
+pub mod tpl;
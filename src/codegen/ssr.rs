@@ -0,0 +1,539 @@
+use swc_core::atoms::Atom;
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::*;
+
+use crate::ast::*;
+use crate::common::*;
+use crate::parser::ir::*;
+
+use super::tpl::{tpl_error_stub, tpl_lit_obj, tpl_prop_obj};
+
+/// 没有子节点和结束标签的 html 元素。
+const VOID_ELEMENTS: [&str; 13] = [
+  "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+  "wbr",
+];
+/// 内容是原始文本的 html 元素，其中的 jsx 文本不需要转义，否则 `a > b` 这样的样式会被破坏。
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+/// 文本节点之前的分隔标记。相邻的文本在 html 中会合并为一个文本节点，空字符串则不会产生文本节点，
+/// hydrate 时需要通过这个标记找到每个文本节点（参看 DomCodegen::hydrate）。
+const TEXT_MARKER: &str = "<!---->";
+/// `If`、`For` 等内容会变化的区块的开始和结束标记，hydrate 时运行时通过这两个标记确定区块的范围。
+const BLOCK_START: &str = "<!--[-->";
+const BLOCK_END: &str = "<!--]-->";
+
+/// 转义 html 文本和双引号包裹的属性值。
+fn escape_html(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  s.chars().for_each(|c| match c {
+    '&' => out.push_str("&amp;"),
+    '<' => out.push_str("&lt;"),
+    '>' => out.push_str("&gt;"),
+    '"' => out.push_str("&quot;"),
+    '\'' => out.push_str("&#39;"),
+    _ => out.push(c),
+  });
+  out
+}
+
+fn call(import: &JingeImport, args: impl IntoIterator<Item = Box<Expr>>) -> Box<Expr> {
+  ast_create_expr_call(
+    ast_create_expr_ident(import.local()),
+    args.into_iter().map(ast_create_arg_expr).collect(),
+  )
+}
+
+/// 拼接 html 字符串的片段，编译期已知的相邻字符串合并为一个字面量。
+enum Part {
+  Str(String),
+  Expr(Box<Expr>),
+}
+
+#[derive(Default)]
+struct Html {
+  parts: Vec<Part>,
+}
+impl Html {
+  fn push_str(&mut self, s: &str) {
+    if let Some(Part::Str(last)) = self.parts.last_mut() {
+      last.push_str(s);
+    } else {
+      self.parts.push(Part::Str(s.to_string()));
+    }
+  }
+  fn push_expr(&mut self, e: Box<Expr>) {
+    self.parts.push(Part::Expr(e));
+  }
+  /// 生成 `"<div>" + ssrText$jg$(...) + "</div>"` 形式的字符串拼接表达式。
+  fn into_expr(self) -> Box<Expr> {
+    let mut parts = self.parts.into_iter().map(|p| match p {
+      Part::Str(s) => ast_create_expr_lit_str(s.into()),
+      Part::Expr(e) => e,
+    });
+    let Some(first) = parts.next() else {
+      return ast_create_expr_lit_str(JINGE_EMPTY_STR.clone());
+    };
+    parts.fold(first, |left, right| {
+      Box::new(Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::Add,
+        left,
+        right,
+      }))
+    })
+  }
+}
+
+/// 服务端渲染（SSR）的代码生成：把模板 IR 生成为返回 html 字符串的表达式。
+/// 所有绑定都直接使用原始表达式求值一次，不创建 watcher；事件和 ref 在服务端没有意义，直接忽略。
+/// 组件、Slot、列表和国际化文本通过 `ssrRenderComponent` 等运行时函数渲染为字符串。
+///
+/// 输出的 html 带有 hydrate 需要的标记：动态文本以及可能和前面的文本合并的文本之前是 [TEXT_MARKER]，
+/// `If`、`For` 和富文本的内容包裹在 [BLOCK_START] 和 [BLOCK_END] 之间。
+pub struct SsrCodegen {
+  html: Html,
+  /// 是否在组件的根渲染作用域（而不是 Slot 函数）中，参看 [ast_create_id_of_container]
+  root_container: bool,
+  /// 下一个常量文本之前是否需要 [TEXT_MARKER]。只有在 html 元素的第一个子节点或者紧跟在 html 元素之后时，才确定不会和前面的文本合并。
+  text_marker: bool,
+}
+
+impl Default for SsrCodegen {
+  fn default() -> Self {
+    Self {
+      html: Html::default(),
+      root_container: true,
+      text_marker: true,
+    }
+  }
+}
+
+impl SsrCodegen {
+  /// 生成组件 return 的 html 字符串表达式。
+  pub fn gen(mut self, nodes: Vec<Node>) -> Box<Expr> {
+    nodes.into_iter().for_each(|n| self.gen_node(n));
+    self.html.into_expr()
+  }
+  fn gen_children(&mut self, nodes: Vec<Node>, root_container: bool) -> Box<Expr> {
+    let current = std::mem::take(&mut self.html);
+    let current_root = std::mem::replace(&mut self.root_container, root_container);
+    let current_marker = std::mem::replace(&mut self.text_marker, true);
+    nodes.into_iter().for_each(|n| self.gen_node(n));
+    self.root_container = current_root;
+    self.text_marker = current_marker;
+    std::mem::replace(&mut self.html, current).into_expr()
+  }
+  /// `this[CONTEXT$jg$]` 或 `host$jg$[CONTEXT$jg$]`
+  fn context(&self) -> Box<Expr> {
+    ast_create_expr_member(
+      ast_create_id_of_container(self.root_container),
+      MemberProp::Computed(ComputedPropName {
+        span: DUMMY_SP,
+        expr: ast_create_expr_ident(JINGE_IMPORT_CONTEXT.local()),
+      }),
+    )
+  }
+
+  fn gen_node(&mut self, node: Node) {
+    match node {
+      Node::Element(el) => {
+        self.gen_element(el);
+        self.text_marker = false;
+        return;
+      }
+      Node::Text(text) => return self.gen_text(text, false),
+      Node::TextBind { expr, .. } => return self.gen_text(expr, false),
+      Node::Component(c) => self.gen_component(&JINGE_IMPORT_SSR_RENDER_COMPONENT, c),
+      // `ssrRenderDynamic$jg$(this[CONTEXT$jg$], component, props, slots)`，component 是字符串时渲染为 html 元素
+      Node::Dynamic(d) => self.gen_component(
+        &JINGE_IMPORT_SSR_RENDER_DYNAMIC,
+        Component {
+          tag: d.component.into_expr(),
+          attrs: d.attrs,
+          spread: None,
+          ref_prop: d.ref_prop,
+          slots: d.slots,
+        },
+      ),
+      Node::RenderSlot(s) => self.gen_render_slot(s),
+      Node::If(c) => self.gen_if(c),
+      Node::For(f) => self.gen_for(f),
+      Node::Intl(intl) => self.gen_intl(intl),
+      Node::Error(code) => self.html.push_expr(tpl_error_stub(code)),
+    }
+    // 组件等输出的 html 可能以文本结尾
+    self.text_marker = true;
+  }
+
+  /// 文本节点。raw 表示在 script、style 中，既不转义也不输出标记。
+  fn gen_text(&mut self, text: Box<Expr>, raw: bool) {
+    let is_str = matches!(text.as_ref(), Expr::Lit(Lit::Str(_)));
+    if !raw && (!is_str || self.text_marker) {
+      self.html.push_str(TEXT_MARKER);
+    }
+    self.text_marker = true;
+    match text.as_ref() {
+      Expr::Lit(Lit::Str(s)) if raw => self.html.push_str(&s.value),
+      Expr::Lit(Lit::Str(s)) => self.html.push_str(&escape_html(&s.value)),
+      _ => self
+        .html
+        .push_expr(call(&JINGE_IMPORT_SSR_TEXT, vec![text])),
+    }
+  }
+
+  fn gen_element(&mut self, mut el: Element) {
+    let tag = el.tag.sym.to_string();
+    self.html.push_str("<");
+    self.html.push_str(&tag);
+    // 有按条件切换的 class 时，class 属性和它们合并后统一输出。
+    let class = if el.class_toggles.is_empty() {
+      None
+    } else {
+      el.attrs
+        .iter()
+        .position(|(name, _)| JINGE_CLASS.eq(&name.sym))
+        .map(|i| el.attrs.remove(i).1.into_expr())
+    };
+    el.attrs.into_iter().for_each(|(name, value)| {
+      let value = value.into_expr();
+      match value.as_ref() {
+        Expr::Lit(Lit::Str(s)) => {
+          self
+            .html
+            .push_str(&format!(" {}=\"{}\"", name.sym, escape_html(&s.value)));
+        }
+        Expr::Lit(Lit::Bool(b)) if b.value => self.html.push_str(&format!(" {}", name.sym)),
+        Expr::Lit(Lit::Bool(_)) | Expr::Lit(Lit::Null(_)) => (),
+        _ => self.html.push_expr(call(
+          &JINGE_IMPORT_SSR_ATTR,
+          vec![ast_create_expr_lit_str(name.sym), value],
+        )),
+      }
+    });
+    if !el.class_toggles.is_empty() {
+      self.gen_class(class, el.class_toggles);
+    }
+    if !el.style.is_empty() {
+      self.gen_style(el.style);
+    }
+    self.html.push_str(">");
+    if el.children.is_empty() && VOID_ELEMENTS.contains(&tag.as_str()) {
+      return;
+    }
+    let raw_text = RAW_TEXT_ELEMENTS.contains(&tag.as_str());
+    self.text_marker = false;
+    el.children.into_iter().for_each(|child| match child {
+      Node::Text(t) => self.gen_text(t, raw_text),
+      Node::TextBind { expr, .. } => self.gen_text(expr, raw_text),
+      c => self.gen_node(c),
+    });
+    self.html.push_str("</");
+    self.html.push_str(&tag);
+    self.html.push_str(">");
+  }
+
+  /// 按条件切换的 class 转换为 `(this.on ? " active" : "")` 拼接到 class 属性中。
+  /// class 属性是字符串常量（或者没有）时直接输出 `class="btn" + ... + "\""`，否则整体作为 ssrAttr$jg$ 的值。
+  fn gen_class(&mut self, class: Option<Box<Expr>>, toggles: Vec<(Atom, Value)>) {
+    let static_class = match class.as_deref() {
+      None => Some(String::new()),
+      Some(Expr::Lit(Lit::Str(s))) => Some(s.value.to_string()),
+      _ => None,
+    };
+    let has_prefix = class.is_some();
+    let toggles = toggles.into_iter().enumerate().map(|(i, (name, cond))| {
+      let name = if has_prefix || i > 0 {
+        format!(" {}", name)
+      } else {
+        name.to_string()
+      };
+      (name, cond.into_expr())
+    });
+    let toggle = |name: String, cond: Box<Expr>| {
+      Box::new(Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: cond,
+        cons: ast_create_expr_lit_str(name.into()),
+        alt: ast_create_expr_lit_str(JINGE_EMPTY_STR.clone()),
+      }))
+    };
+    if let Some(static_class) = static_class {
+      self
+        .html
+        .push_str(&format!(" class=\"{}", escape_html(&static_class)));
+      toggles.for_each(|(name, cond)| {
+        self.html.push_expr(toggle(escape_html(&name), cond));
+      });
+      self.html.push_str("\"");
+      return;
+    }
+    let value = toggles.fold(class.unwrap(), |left, (name, cond)| {
+      Box::new(Expr::Bin(BinExpr {
+        span: DUMMY_SP,
+        op: BinaryOp::Add,
+        left,
+        right: toggle(name, cond),
+      }))
+    });
+    self.html.push_expr(call(
+      &JINGE_IMPORT_SSR_ATTR,
+      vec![ast_create_expr_lit_str(JINGE_CLASS.clone()), value],
+    ));
+  }
+
+  /// style 对象：全部是常量时直接输出 `style="color:red;width:10px"`，
+  /// 否则输出 `ssrAttr$jg$("style", ssrStyle$jg$({ "font-size": this.size }))`，由运行时拼接并忽略 null/undefined 的属性。
+  fn gen_style(&mut self, style: Vec<(Atom, Value)>) {
+    let style: Vec<_> = style
+      .into_iter()
+      .map(|(name, value)| (name, value.into_expr()))
+      .collect();
+    let is_static = style
+      .iter()
+      .all(|(_, v)| matches!(v.as_ref(), Expr::Lit(Lit::Str(_)) | Expr::Lit(Lit::Num(_))));
+    if is_static {
+      let css = style
+        .iter()
+        .map(|(name, value)| match value.as_ref() {
+          Expr::Lit(Lit::Str(s)) => format!("{}:{}", name, s.value),
+          Expr::Lit(Lit::Num(n)) => format!("{}:{}", name, n.value),
+          _ => unreachable!(),
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+      self
+        .html
+        .push_str(&format!(" style=\"{}\"", escape_html(&css)));
+      return;
+    }
+    let obj = tpl_lit_obj(
+      style
+        .into_iter()
+        .map(|(name, value)| (IdentName::from(name), value))
+        .collect(),
+    );
+    self.html.push_expr(call(
+      &JINGE_IMPORT_SSR_ATTR,
+      vec![
+        ast_create_expr_lit_str(JINGE_STYLE.clone()),
+        call(&JINGE_IMPORT_SSR_STYLE, vec![obj]),
+      ],
+    ));
+  }
+
+  /// Slot 函数：`(host$jg$, ...params) => "..."`
+  fn gen_slot_fn(&mut self, slot: Slot) -> Box<Expr> {
+    let mut params = vec![Pat::Ident(BindingIdent::from(JINGE_HOST_IDENT.ident()))];
+    params.extend(slot.params);
+    let body = self.gen_children(slot.children, false);
+    ast_create_expr_arrow_fn(params, Box::new(BlockStmtOrExpr::Expr(body)))
+  }
+
+  /// 组件的 Slot 参数：只有默认 Slot 时是 Slot 函数，有命名 Slot 时是 Slot 函数组成的对象，没有 Slot 时为 None。
+  fn gen_slots(&mut self, mut slots: Vec<Slot>) -> Option<Box<Expr>> {
+    if slots.len() > 1 {
+      let named: Vec<_> = slots
+        .into_iter()
+        .skip(1)
+        .filter(|s| !s.children.is_empty())
+        .map(|s| (IdentName::from(s.name.clone()), self.gen_slot_fn(s)))
+        .collect();
+      Some(tpl_lit_obj(named))
+    } else {
+      slots
+        .pop()
+        .filter(|s| !s.children.is_empty())
+        .map(|s| self.gen_slot_fn(s))
+    }
+  }
+
+  /// `ssrRenderComponent$jg$(this[CONTEXT$jg$], A, { a: this.b }, (host$jg$) => "...")`
+  fn gen_component(&mut self, render: &JingeImport, c: Component) {
+    let props = if let Some(spread) = c.spread {
+      Some(ast_create_expr_ident(spread))
+    } else if c.attrs.is_empty() {
+      None
+    } else {
+      Some(tpl_lit_obj(
+        c.attrs
+          .into_iter()
+          .map(|(name, value)| (name, value.into_expr()))
+          .collect(),
+      ))
+    };
+    let mut args = vec![self.context(), c.tag];
+    let slots = self.gen_slots(c.slots);
+    match (props, slots) {
+      (props, Some(slots)) => {
+        args.push(props.unwrap_or_else(|| ast_create_expr_ident(JINGE_UNDEFINED.clone().into())));
+        args.push(slots);
+      }
+      (Some(props), None) => args.push(props),
+      (None, None) => (),
+    }
+    self.html.push_expr(call(render, args));
+  }
+
+  /// 条件渲染只需要求值一次：`expect ? "..." : "..."`
+  fn gen_if(&mut self, c: If) {
+    let mut cons = None;
+    let mut alt = None;
+    c.slots
+      .into_iter()
+      .skip(1)
+      .for_each(|s| match s.name.as_str() {
+        "true" => cons = Some(s.children),
+        _ => alt = Some(s.children),
+      });
+    let root_container = self.root_container;
+    let mut branch = |children: Option<Vec<Node>>| {
+      children
+        .map(|c| self.gen_children(c, root_container))
+        .unwrap_or_else(|| ast_create_expr_lit_str(JINGE_EMPTY_STR.clone()))
+    };
+    let cons = branch(cons);
+    let alt = branch(alt);
+    self.html.push_str(BLOCK_START);
+    self.html.push_expr(Box::new(Expr::Paren(ParenExpr {
+      span: DUMMY_SP,
+      expr: Box::new(Expr::Cond(CondExpr {
+        span: DUMMY_SP,
+        test: c.expect.into_expr(),
+        cons,
+        alt,
+      })),
+    })));
+    self.html.push_str(BLOCK_END);
+  }
+
+  /// `ssrRenderFor$jg$(this, list, (host$jg$, each$jg$0) => "...")`，keyFn 在服务端没有意义，直接忽略。
+  fn gen_for(&mut self, f: For) {
+    let mut args = vec![
+      ast_create_id_of_container(self.root_container),
+      f.looop.into_expr(),
+    ];
+    if let Some(slot) = self.gen_slots(f.slots) {
+      args.push(slot);
+    }
+    self.html.push_str(BLOCK_START);
+    self
+      .html
+      .push_expr(call(&JINGE_IMPORT_SSR_RENDER_FOR, args));
+    self.html.push_str(BLOCK_END);
+  }
+
+  /// `ssrRenderSlot$jg$(this, this[SLOTS$jg$][DEFAULT_SLOT$jg$], { a: 1 })`
+  fn gen_render_slot(&mut self, s: RenderSlot) {
+    let slot = ast_create_expr_member(
+      ast_create_expr_member(
+        ast_create_expr_this(),
+        MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: ast_create_expr_ident(JINGE_IMPORT_SLOTS.local()),
+        }),
+      ),
+      if let Some(slot_name) = s.name {
+        MemberProp::Ident(IdentName::from(slot_name))
+      } else {
+        MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: ast_create_expr_ident(JINGE_IMPORT_DEFAULT_SLOT.local()),
+        })
+      },
+    );
+    let mut args = vec![ast_create_id_of_container(self.root_container), slot];
+    if let Some(spread) = s.spread {
+      args.push(ast_create_expr_ident(spread));
+    } else if !s.args.is_empty() {
+      args.push(tpl_prop_obj(
+        s.args
+          .into_iter()
+          .map(|(k, v)| (k, v.into_expr()))
+          .collect(),
+      ));
+    }
+    self
+      .html
+      .push_expr(call(&JINGE_IMPORT_SSR_RENDER_SLOT, args));
+  }
+
+  /// `ssrIntlText$jg$(this, key, params, defaultText)`，参数和 DOM 渲染的 renderIntlText 一致。
+  fn gen_intl(&mut self, intl: Intl) {
+    let mut args = vec![
+      ast_create_id_of_container(self.root_container),
+      ast_create_expr_lit_str(intl.key),
+    ];
+    let has_params = !intl.params.is_empty();
+    if has_params {
+      args.push(tpl_prop_obj(
+        intl
+          .params
+          .into_iter()
+          .map(|(k, v)| (k, v.into_expr()))
+          .collect(),
+      ));
+    }
+    if let Some(default_text) = intl.default_text {
+      if !has_params {
+        args.push(ast_create_expr_ident(JINGE_UNDEFINED.clone().into()));
+      }
+      args.push(ast_create_expr_lit_str(default_text));
+    }
+    // 普通文本是一个文本节点，富文本包含多个节点，作为区块处理
+    if intl.is_rich_text {
+      self.html.push_str(BLOCK_START);
+      self
+        .html
+        .push_expr(call(&JINGE_IMPORT_SSR_INTL_RICH_TEXT, args));
+      self.html.push_str(BLOCK_END);
+    } else {
+      self.html.push_str(TEXT_MARKER);
+      self.html.push_expr(call(&JINGE_IMPORT_SSR_INTL_TEXT, args));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::common::RenderMode;
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_ssr() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  if (!props.show) return null;
+  return <div class=\"a&b\" title={props.t} onClick={() => {}}>
+    <br />
+    {props.name}
+    {props.ok ? <b>yes</b> : <i>no</i>}
+    <ul>{props.list.map((item) => <li key={item}>{item}</li>)}</ul>
+    <Item a={props.a}><span>x</span></Item>
+  </div>;
+}"
+      .into(),
+      TransformOptions {
+        render_mode: RenderMode::Ssr,
+        ..Default::default()
+      },
+    );
+    assert!(diagnostics.is_empty());
+    assert!(code.contains("ssrText as ssrText$jg$"));
+    assert!(!code.contains("createEle"));
+    assert!(!code.contains("addEvent"));
+    assert!(!code.contains("Watcher"));
+    assert!(code.contains(r#"return "";"#));
+    assert!(code.contains(
+      r#"return '<div class="a&amp;b"' + ssrAttr$jg$("title", props.t) + "><br><!---->" + ssrText$jg$(props.name) + "<!--[-->" + (props.ok ? "<b>yes</b>" : "<i>no</i>") + "<!--]--><ul><!--[-->" + ssrRenderFor$jg$(this, props.list, (host$jg$, each$jg$"#
+    ));
+    assert!(code.contains(r#"=>"<li><!---->" + ssrText$jg$(each$jg$"#));
+    assert!(code.contains(
+      r#".data) + "</li>") + "<!--]--></ul>" + ssrRenderComponent$jg$(this[CONTEXT$jg$], Item, {"#
+    ));
+    assert!(code.contains(r#"}, (host$jg$)=>"<span>x</span>") + "</div>";"#));
+  }
+}
@@ -0,0 +1,461 @@
+use swc_core::{
+  atoms::Atom,
+  common::{SyntaxContext, DUMMY_SP},
+  ecma::ast::*,
+};
+
+use crate::{ast::*, common::*};
+
+use crate::parser::ir::{Event, ExprParseResult, Namespace};
+
+/// 编译出错时的占位代码：`(() => { throw new Error("[JG2005] ...") })()`。
+/// 模板的其余部分仍然正常编译，运行到出错的位置时才会抛出异常。
+pub fn tpl_error_stub(code: DiagCode) -> Box<Expr> {
+  let err = Box::new(Expr::New(NewExpr {
+    span: DUMMY_SP,
+    ctxt: SyntaxContext::empty(),
+    callee: ast_create_expr_ident(global_ident("Error".into())),
+    args: Some(vec![ast_create_arg_expr(ast_create_expr_lit_str(
+      format!("[{}] {}", code.code(), code.message(&[])).into(),
+    ))]),
+    type_args: None,
+  }));
+  ast_create_expr_call(
+    ast_create_expr_arrow_fn(
+      vec![],
+      Box::new(BlockStmtOrExpr::BlockStmt(BlockStmt {
+        span: DUMMY_SP,
+        ctxt: SyntaxContext::empty(),
+        stmts: vec![Stmt::Throw(ThrowStmt {
+          span: DUMMY_SP,
+          arg: err,
+        })],
+      })),
+    ),
+    vec![],
+  )
+}
+
+pub fn tpl_set_ref_code(r: Box<Expr>, el: Ident) -> Box<Expr> {
+  let args = vec![
+    ast_create_arg_expr(ast_create_expr_this()),
+    ast_create_arg_expr(r),
+    ast_create_arg_expr(ast_create_expr_ident(el)),
+  ];
+
+  ast_create_expr_call(ast_create_expr_ident(JINGE_IMPORT_SET_REF.local()), args)
+}
+
+pub fn tpl_push_el_code(root: bool, is_root_container: bool, el: Ident) -> Box<Expr> {
+  let args = vec![ast_create_arg_expr(ast_create_expr_ident(el))];
+  Box::new(Expr::Call(CallExpr {
+    ctxt: SyntaxContext::empty(),
+    span: DUMMY_SP,
+    callee: Callee::Expr(ast_create_expr_member(
+      ast_create_expr_member(
+        ast_create_id_of_container(is_root_container),
+        MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: ast_create_expr_ident(if root {
+            JINGE_IMPORT_ROOT_NODES.local()
+          } else {
+            JINGE_IMPORT_NON_ROOT_COMPONENT_NODES.local()
+          }),
+        }),
+      ),
+      MemberProp::Ident(IdentName::from("push")),
+    )),
+    args,
+    type_args: None,
+  }))
+}
+
+pub fn tpl_add_event(el: Ident, evt: Event) -> Box<Expr> {
+  let mut args = vec![
+    ast_create_arg_expr(ast_create_expr_ident(el)),
+    ast_create_arg_expr(ast_create_expr_lit_str(evt.event_name)),
+    ast_create_arg_expr(evt.event_handler),
+  ];
+  if evt.capture {
+    args.push(ast_create_arg_expr(ast_create_expr_lit_bool(true)));
+  }
+  ast_create_expr_call(ast_create_expr_ident(JINGE_IMPORT_ADD_EVENT.local()), args)
+}
+
+pub fn tpl_lit_obj(lit_arr: Vec<(IdentName, Box<Expr>)>) -> Box<Expr> {
+  Box::new(Expr::Object(ObjectLit {
+    span: DUMMY_SP,
+    props: lit_arr
+      .into_iter()
+      .map(|(prop, value)| {
+        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+          key: PropName::Str(Str::from(prop.sym)),
+          value,
+        })))
+      })
+      .collect(),
+  }))
+}
+
+pub fn tpl_prop_obj(props: Vec<(PropName, Box<Expr>)>) -> Box<Expr> {
+  Box::new(Expr::Object(ObjectLit {
+    span: DUMMY_SP,
+    props: props
+      .into_iter()
+      .map(|(key, value)| PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp { key, value }))))
+      .collect(),
+  }))
+}
+
+pub fn tpl_set_attribute(el: Box<Expr>, attr_name: Atom, attr_value: Box<Expr>) -> Box<Expr> {
+  ast_create_expr_call(
+    ast_create_expr_ident(JINGE_IMPORT_SET_ATTRIBUTE.local()),
+    vec![
+      ast_create_arg_expr(el),
+      ast_create_arg_expr(Box::new(Expr::Lit(Lit::Str(Str::from(attr_name))))),
+      ast_create_arg_expr(attr_value),
+    ],
+  )
+}
+
+pub fn tpl_render_const_text(
+  c: Box<Expr>,
+  is_parent_component: bool,
+  is_root_container: bool,
+) -> Box<Expr> {
+  if is_parent_component {
+    ast_create_expr_call(
+      ast_create_expr_ident(JINGE_IMPORT_TEXT_RENDER_FN.local()),
+      vec![
+        ast_create_arg_expr(ast_create_id_of_container(is_root_container)),
+        ast_create_arg_expr(c),
+      ],
+    )
+  } else {
+    c
+  }
+}
+
+/// 国际化文本的渲染代码。hydrate 时 cursor 是遍历已有 DOM 节点的游标，使用对应的 hydrate 版本的运行时函数。
+pub fn tpl_render_intl_text(
+  cursor: Option<Ident>,
+  is_rich: bool,
+  key: Atom,
+  params: Option<ExprOrSpread>,
+  default_text: Option<&Atom>,
+  is_parent_component: bool,
+  is_root_container: bool,
+) -> Box<Expr> {
+  let hydrate = cursor.is_some();
+  let mut args: Vec<_> = cursor
+    .map(|c| ast_create_arg_expr(ast_create_expr_ident(c)))
+    .into_iter()
+    .collect();
+  args.extend([
+    ast_create_arg_expr(ast_create_id_of_container(is_root_container)),
+    ast_create_arg_expr(ast_create_expr_lit_bool(is_parent_component)),
+    ast_create_arg_expr(ast_create_expr_lit_str(key)),
+  ]);
+  let has_params = params.is_some();
+  if let Some(params) = params {
+    args.push(params);
+  }
+  if let Some(default_text) = default_text {
+    if !has_params {
+      args.push(ast_create_arg_expr(Box::new(Expr::Ident(Ident::from(
+        JINGE_UNDEFINED.clone(),
+      )))));
+    }
+    args.push(ast_create_arg_expr(ast_create_expr_lit_str(
+      default_text.clone(),
+    )));
+  }
+  ast_create_expr_call(
+    ast_create_expr_ident(match (is_rich, has_params, hydrate) {
+      (true, _, false) => JINGE_IMPORT_RENDER_INTL_RICH_TEXT.local(),
+      (true, _, true) => JINGE_IMPORT_HYDRATE_INTL_RICH_TEXT.local(),
+      (false, true, false) => JINGE_IMPORT_RENDER_INTL_TEXT_WITH_PARAMS.local(),
+      (false, true, true) => JINGE_IMPORT_HYDRATE_INTL_TEXT_WITH_PARAMS.local(),
+      (false, false, false) => JINGE_IMPORT_RENDER_INTL_TEXT.local(),
+      (false, false, true) => JINGE_IMPORT_HYDRATE_INTL_TEXT.local(),
+    }),
+    args,
+  )
+}
+
+/// 文本节点 el 监听表达式变更后更新文本内容的渲染语句。node 是得到文本节点的表达式，
+/// 通常是 `createTextNode$jg$("")`，hydrate 时是 `claimText$jg$(cur$jg$0)`。
+pub fn tpl_render_expr_text(
+  el: Ident,
+  node: Box<Expr>,
+  expr_result: ExprParseResult,
+  value: Box<Expr>,
+  is_parent_component: bool,
+  is_root_container: bool,
+) -> Vec<Stmt> {
+  let render_fn = ast_create_expr_call(
+    ast_create_expr_ident(JINGE_IMPORT_SET_TEXT_CONTENT.local()),
+    vec![
+      ast_create_arg_expr(ast_create_expr_ident(el.clone())),
+      ast_create_arg_expr(value),
+    ],
+  );
+
+  let mut stmts = vec![
+    ast_create_stmt_decl_const(el.clone(), node),
+    Stmt::Expr(ExprStmt {
+      span: DUMMY_SP,
+      expr: tpl_watch_and_render(render_fn, expr_result, is_root_container),
+    }),
+  ];
+
+  if is_parent_component {
+    stmts.push(Stmt::Expr(ExprStmt {
+      span: DUMMY_SP,
+      expr: tpl_push_el_code(true, is_root_container, el),
+    }));
+  }
+  stmts
+}
+
+pub fn tpl_watch_and_render(
+  render_fn_body: Box<Expr>,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+) -> Box<Expr> {
+  match expr_result {
+    ExprParseResult::None => unreachable!(),
+    ExprParseResult::Complex(watch_expr) => {
+      let args = vec![
+        ast_create_arg_expr(watch_expr),
+        ast_create_arg_expr(ast_create_expr_arrow_fn(
+          vec![Pat::Ident(BindingIdent::from(JINGE_V_IDENT.ident()))],
+          Box::new(BlockStmtOrExpr::Expr(render_fn_body)),
+        )),
+        // 复杂表达式，会有 PathWatcher/ExprWatcher 等的封装，统一加到 [HOST_WATCH] 中，在 host component 销毁时卸载。
+        ast_create_arg_expr(ast_create_id_of_container(is_root_container)),
+      ];
+      ast_create_expr_call(
+        ast_create_expr_ident(JINGE_IMPORT_WATCH_FOR_RENDER.local()),
+        args,
+      )
+    }
+    ExprParseResult::Simple(sr) => {
+      let mut args = vec![
+        ast_create_arg_expr(sr.vm),
+        ast_create_arg_expr(sr.path),
+        ast_create_arg_expr(ast_create_expr_arrow_fn(
+          vec![Pat::Ident(BindingIdent::from(JINGE_V_IDENT.ident()))],
+          Box::new(BlockStmtOrExpr::Expr(render_fn_body)),
+        )),
+      ];
+      if sr.not_op > 0 {
+        args.push(ast_create_arg_expr(Box::new(Expr::Lit(Lit::Num(
+          Number::from(sr.not_op as usize),
+        )))));
+      }
+      if !is_root_container || !sr.is_this {
+        // 简单表达式，如果不是 root container，说明一定有 Slot 传递的 host component，需要添加 [HOST WATCH]
+        // 如果表达式不是 this. 打头的，也就是监听的可能是全局 vm，或监听的 Slot 传递进来的 vm。
+        // 如果监听全局 vm，则要把对全局变量 vm 的监听放到 this 组件的 [HOST_WATCH]中，在 this 组件销毁时卸载。
+        // 如果监听的传递进来的 vm ，也同理。
+        args.push(ast_create_arg_expr(ast_create_id_of_container(
+          is_root_container,
+        )));
+      }
+      ast_create_expr_call(
+        ast_create_expr_ident(if sr.not_op > 0 {
+          JINGE_IMPORT_WATCH_PATH_FOR_RENDER_2.local()
+        } else {
+          JINGE_IMPORT_WATCH_PATH_FOR_RENDER.local()
+        }),
+        args,
+      )
+    }
+  }
+}
+
+/// 设置 html 元素的属性，IDL 属性（例如 `value`、`checked`）直接赋值，其它属性调用 setAttribute。
+/// 创建 ns 命名空间的元素的运行时函数，有常量属性时使用带 A 后缀的版本。
+pub fn tpl_create_ele_callee(ns: Namespace, with_attrs: bool) -> Ident {
+  match (ns, with_attrs) {
+    (Namespace::Html, false) => JINGE_IMPORT_CREATE_ELE.local(),
+    (Namespace::Html, true) => JINGE_IMPORT_CREATE_ELE_A.local(),
+    (Namespace::Svg, false) => JINGE_IMPORT_CREATE_SVG_ELE.local(),
+    (Namespace::Svg, true) => JINGE_IMPORT_CREATE_SVG_ELE_A.local(),
+    (Namespace::MathMl, false) => JINGE_IMPORT_CREATE_MATH_ELE.local(),
+    (Namespace::MathMl, true) => JINGE_IMPORT_CREATE_MATH_ELE_A.local(),
+  }
+}
+
+/// `xlink:href`、`xml:lang` 等带前缀的属性所在的命名空间，这些属性需要使用 setAttributeNS 设置。
+pub fn attr_namespace(attr_name: &str) -> Option<&'static str> {
+  let (prefix, _) = attr_name.split_once(':')?;
+  match prefix {
+    "xlink" => Some("http://www.w3.org/1999/xlink"),
+    "xml" => Some("http://www.w3.org/XML/1998/namespace"),
+    "xmlns" => Some("http://www.w3.org/2000/xmlns/"),
+    _ => None,
+  }
+}
+
+pub fn tpl_set_html_attr(el: Ident, attr_name: Atom, value: Box<Expr>) -> Box<Expr> {
+  if let Some(ns) = attr_namespace(&attr_name) {
+    ast_create_expr_call(
+      ast_create_expr_ident(JINGE_IMPORT_SET_ATTRIBUTE_NS.local()),
+      vec![
+        ast_create_arg_expr(ast_create_expr_ident(el)),
+        ast_create_arg_expr(ast_create_expr_lit_str(ns.into())),
+        ast_create_arg_expr(ast_create_expr_lit_str(attr_name)),
+        ast_create_arg_expr(value),
+      ],
+    )
+  } else if IDL_ATTRIBUTE_SET.binary_search(&attr_name).is_ok() {
+    ast_create_expr_assign_mem(ast_create_expr_ident(el), attr_name, value)
+  } else {
+    tpl_set_attribute(ast_create_expr_ident(el), attr_name, value)
+  }
+}
+
+/// `el.style.setProperty("font-size", value)`，参看 [crate::parser::ir::Element::style]
+pub fn tpl_set_style(el: Ident, name: Atom, value: Box<Expr>) -> Box<Expr> {
+  ast_create_expr_call(
+    ast_create_expr_member(
+      ast_create_expr_member(
+        ast_create_expr_ident(el),
+        MemberProp::Ident(IdentName::from(JINGE_STYLE.clone())),
+      ),
+      MemberProp::Ident(IdentName::from("setProperty")),
+    ),
+    vec![
+      ast_create_arg_expr(ast_create_expr_lit_str(name)),
+      ast_create_arg_expr(value),
+    ],
+  )
+}
+
+/// 只监听单个 css 属性的表达式，变更时只更新该属性。
+pub fn tpl_watch_and_set_style(
+  name: Atom,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  el: Ident,
+) -> Box<Expr> {
+  let set_fn = tpl_set_style(el, name, ast_create_expr_ident(JINGE_V_IDENT.ident()));
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
+
+/// `el.classList.toggle("active", !!value)`，参看 [crate::parser::ir::Element::class_toggles]。
+/// 第二个参数为 undefined 时 toggle 会反转当前状态，所以需要转换为 bool。
+pub fn tpl_toggle_class(el: Ident, name: Atom, value: Box<Expr>) -> Box<Expr> {
+  let not = |arg| {
+    Box::new(Expr::Unary(UnaryExpr {
+      span: DUMMY_SP,
+      op: UnaryOp::Bang,
+      arg,
+    }))
+  };
+  ast_create_expr_call(
+    ast_create_expr_member(
+      ast_create_expr_member(
+        ast_create_expr_ident(el),
+        MemberProp::Ident(IdentName::from("classList")),
+      ),
+      MemberProp::Ident(IdentName::from("toggle")),
+    ),
+    vec![
+      ast_create_arg_expr(ast_create_expr_lit_str(name)),
+      ast_create_arg_expr(not(not(value))),
+    ],
+  )
+}
+
+/// 只监听单个 class 的条件表达式，变更时只切换该 class，不影响其它 class。
+pub fn tpl_watch_and_toggle_class(
+  name: Atom,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  el: Ident,
+) -> Box<Expr> {
+  let set_fn = tpl_toggle_class(el, name, ast_create_expr_ident(JINGE_V_IDENT.ident()));
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
+
+/// 多选 select 的 `bind:value`，参看 [crate::parser::ir::Element::select_values]
+pub fn tpl_watch_and_set_select_values(
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  el: Ident,
+) -> Box<Expr> {
+  let set_fn = ast_create_expr_call(
+    ast_create_expr_ident(JINGE_IMPORT_SET_SELECT_VALUES.local()),
+    vec![
+      ast_create_arg_expr(ast_create_expr_ident(el)),
+      ast_create_arg_expr(ast_create_expr_ident(JINGE_V_IDENT.ident())),
+    ],
+  );
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
+
+pub fn tpl_watch_and_set_html_attr(
+  attr_name: IdentName,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  el: Ident,
+) -> Box<Expr> {
+  let set_fn = tpl_set_html_attr(
+    el,
+    attr_name.sym,
+    ast_create_expr_ident(JINGE_V_IDENT.ident()),
+  );
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
+
+pub fn tpl_watch_and_set_component_attr(
+  attr_name: IdentName,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  attrs: Ident,
+) -> Box<Expr> {
+  let set_fn = ast_create_expr_assign_mem(
+    ast_create_expr_ident(attrs),
+    attr_name.sym,
+    ast_create_expr_ident(JINGE_V_IDENT.ident()),
+  );
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
+
+/// 监听表达式变更并设置 vm 上的属性，用于 Slot 参数和国际化文本参数等 key 可能不是 Ident 的场景。
+pub fn tpl_watch_and_set_vm_prop(
+  prop: PropName,
+  expr_result: ExprParseResult,
+  is_root_container: bool,
+  vm: Ident,
+) -> Box<Expr> {
+  let set_fn = Box::new(Expr::Assign(AssignExpr {
+    span: DUMMY_SP,
+    op: AssignOp::Assign,
+    left: AssignTarget::Simple(SimpleAssignTarget::Member(MemberExpr {
+      span: DUMMY_SP,
+      obj: ast_create_expr_ident(vm),
+      prop: match prop {
+        PropName::Ident(id) => MemberProp::Ident(id),
+        PropName::Computed(e) => MemberProp::Computed(e),
+        PropName::Num(x) => MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: Box::new(Expr::Lit(Lit::Num(x))),
+        }),
+        PropName::Str(x) => MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: Box::new(Expr::Lit(Lit::Str(x))),
+        }),
+        PropName::BigInt(x) => MemberProp::Computed(ComputedPropName {
+          span: DUMMY_SP,
+          expr: Box::new(Expr::Lit(Lit::BigInt(x))),
+        }),
+      },
+    })),
+    right: ast_create_expr_ident(JINGE_V_IDENT.ident()),
+  }));
+  tpl_watch_and_render(set_fn, expr_result, is_root_container)
+}
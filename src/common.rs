@@ -1,15 +1,116 @@
+use std::cell::Cell;
+
+use hashbrown::HashSet;
+
 use swc_core::atoms::Atom;
-use swc_core::common::errors::HANDLER;
-use swc_core::common::{Span, DUMMY_SP};
+
+use swc_core::common::{sync::Lrc, Mark, SourceFile, Span, SyntaxContext, DUMMY_SP};
 use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{noop_visit_type, Visit, VisitWith};
 
-#[derive(Clone, Copy)]
+pub use crate::diagnostic::DiagCode;
+use crate::diagnostic::{emit_diagnostic, Severity};
+
+#[derive(Clone, Copy, Default)]
 pub enum IntlType {
+  #[default]
   Disabled,
   Enabled(bool),
 }
+
+/// 模板的编译目标。
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+  /// 在浏览器中创建 DOM 节点，并监听数据变更后更新
+  #[default]
+  Dom,
+  /// 服务端渲染：组件编译为返回转义后的 html 字符串的函数，只渲染一次，不监听变更，也不绑定事件
+  Ssr,
+  /// 接管服务端渲染出的 DOM：按文档顺序查找已有的节点而不是创建节点，然后和 Dom 一样监听变更、绑定事件
+  Hydrate,
+}
+impl RenderMode {
+  /// 解析 options 中的 `mode` 配置，比如 `'dom'`、`'ssr'`、`'hydrate'`。
+  pub fn parse(s: &str) -> Option<Self> {
+    match s {
+      "dom" => Some(Self::Dom),
+      "ssr" => Some(Self::Ssr),
+      "hydrate" => Some(Self::Hydrate),
+      _ => None,
+    }
+  }
+}
+
+/// 把模板节点的 Span 转换为 `src/a.tsx:3:5` 形式的源码位置。
+/// 开发模式下 hydrate 生成的代码带上这个位置，运行时发现和服务端渲染的 DOM 不匹配时报告出错的模板节点。
+#[derive(Clone)]
+pub struct SourceLocator {
+  file: Lrc<SourceFile>,
+}
+impl SourceLocator {
+  pub fn new(file: Lrc<SourceFile>) -> Self {
+    Self { file }
+  }
+  pub fn locate(&self, sp: Span) -> Option<Atom> {
+    if sp.is_dummy() {
+      return None;
+    }
+    let line = self.file.lookup_line(sp.lo)?;
+    let (lo, _) = self.file.line_bounds(line);
+    let start = (lo - self.file.start_pos).0 as usize;
+    let end = (sp.lo - self.file.start_pos).0 as usize;
+    let col = self.file.src[start..end].chars().count() + 1;
+    Some(format!("{}:{}:{}", self.file.name, line + 1, col).into())
+  }
+}
+
+thread_local! {
+  /// 编译器生成的标识符（`$jg$`、`v`、`a0`、`host$jg$` 等）所使用的 SyntaxContext。
+  /// 这些标识符都带有同一个私有的 Mark，最终经过 hygiene 处理后，即便和用户代码中的变量同名也不会互相遮蔽。
+  static PRIVATE_CTXT: Cell<SyntaxContext> = const { Cell::new(SyntaxContext::empty()) };
+  /// resolver 的 unresolved mark 对应的 SyntaxContext，编译器生成的代码引用全局变量时使用，参看 [global_ident]。
+  static UNRESOLVED_CTXT: Cell<SyntaxContext> = const { Cell::new(SyntaxContext::empty()) };
+}
+
+/// 在 f 的执行期间，编译器生成的标识符都带上 mark 对应的 SyntaxContext，引用的全局变量带上 unresolved_mark。
+pub fn with_private_mark<R>(mark: Mark, unresolved_mark: Mark, f: impl FnOnce() -> R) -> R {
+  let prev = PRIVATE_CTXT.with(|c| c.replace(SyntaxContext::empty().apply_mark(mark)));
+  let prev_unresolved =
+    UNRESOLVED_CTXT.with(|c| c.replace(SyntaxContext::empty().apply_mark(unresolved_mark)));
+  let r = f();
+  PRIVATE_CTXT.with(|c| c.set(prev));
+  UNRESOLVED_CTXT.with(|c| c.set(prev_unresolved));
+  r
+}
+
+/// 创建编译器私有的标识符，参看 [with_private_mark]。
+#[inline]
+pub fn private_ident(sym: Atom) -> Ident {
+  Ident::new(sym, DUMMY_SP, PRIVATE_CTXT.with(|c| c.get()))
+}
+
+/// 引用全局变量（例如 `Error`）的标识符。和用户代码中未声明的变量一样带有 unresolved mark，
+/// 因此 hygiene 会重命名用户代码中同名的局部变量，而不是让生成的代码引用到它。
+#[inline]
+pub fn global_ident(sym: Atom) -> Ident {
+  Ident::new(sym, DUMMY_SP, UNRESOLVED_CTXT.with(|c| c.get()))
+}
+
+/// 编译器生成代码中使用的局部变量名，通过 `ident()` 获取带 hygiene 标记的 Ident。
+pub struct JingePrivateIdent(Atom);
+impl JingePrivateIdent {
+  #[inline]
+  fn new(sym: &'static str) -> Self {
+    Self(Atom::from(sym))
+  }
+  #[inline]
+  pub fn ident(&self) -> Ident {
+    private_ident(self.0.clone())
+  }
+}
+
 pub struct JingeImport {
-  local: Ident,
+  local: Atom,
   imported: Ident,
 }
 impl JingeImport {
@@ -22,7 +123,7 @@ impl JingeImport {
   }
   #[inline]
   pub fn local(&self) -> Ident {
-    self.local.clone()
+    private_ident(self.local.clone())
   }
   #[inline]
   pub fn imported(&self) -> Ident {
@@ -38,6 +139,13 @@ macro_rules! x {
   };
 }
 
+/// 渲染代码中元素、节点等局部变量名的前缀，参看 DomCodegen::new_local
+pub const JINGE_EL_PREFIX: &str = "$jg$";
+/// 渲染代码中组件属性、slot 参数等 vm 局部变量名的前缀
+pub const JINGE_ATTR_PREFIX: &str = "attrs$jg$";
+/// hydrate 渲染代码中遍历已有 DOM 节点的游标局部变量名的前缀
+pub const JINGE_CURSOR_PREFIX: &str = "cur$jg$";
+
 // TODO: should use macro to generate
 lazy_static::lazy_static! {
   pub static ref JINGE_IMPORT_TEXT_RENDER_FN: JingeImport = x!("textRenderFn");
@@ -72,17 +180,23 @@ lazy_static::lazy_static! {
   pub static ref JINGE_IMPORT_RENDER_FC: JingeImport = x!("renderFunctionComponent");
   pub static ref JINGE_IMPORT_NON_ROOT_COMPONENT_NODES: JingeImport = x!("NON_ROOT_COMPONENT_NODES");
 
-  pub static ref JINGE_EL_IDENT: Ident = "$jg$".into();
-  pub static ref JINGE_ATTR_IDENT: Ident = "attrs$jg$".into();
   pub static ref JINGE_LOOP_EACH_IDENTS: Vec<Atom> = vec!["each$jg$0".into(), "each$jg$1".into(), "each$jg$2".into()];
   pub static ref JINGE_LOOP_EACH_DATA: Atom = "data".into();
   pub static ref JINGE_LOOP_EACH_INDEX: Atom = "index".into();
-  pub static ref JINGE_HOST_IDENT: Ident = "host$jg$".into();
-  pub static ref JINGE_V_IDENT: Ident = "v".into();
+  pub static ref JINGE_HOST_IDENT: JingePrivateIdent = JingePrivateIdent::new("host$jg$");
+  pub static ref JINGE_V_IDENT: JingePrivateIdent = JingePrivateIdent::new("v");
+  pub static ref JINGE_PROPS_IDENT: JingePrivateIdent = JingePrivateIdent::new("props$jg$");
+  /// 匿名默认导出的组件使用的合成名称，用于 parsed components/HMR 列表。
+  pub static ref JINGE_DEFAULT_COMPONENT: Atom = "default$jg$".into();
   pub static ref JINGE_T: Atom = "t".into();
+  pub static ref JINGE_COMPONENT: Atom = "Component".into();
+  pub static ref JINGE_RENDER: Atom = "render".into();
   pub static ref TEXT_CONTENT: Ident = "textContent".into();
   pub static ref JINGE: Atom = "jinge".into();
   pub static ref JINGE_SVG: Atom = "svg".into();
+  pub static ref JINGE_MATH: Atom = "math".into();
+  pub static ref JINGE_STYLE: Atom = "style".into();
+  pub static ref JINGE_FOREIGN_OBJECT: Atom = "foreignObject".into();
   pub static ref JINGE_REF: Atom = "ref".into();
   pub static ref JINGE_CALL: Atom = "call".into();
   pub static ref JINGE_CHILDREN: Atom = "children".into();
@@ -95,6 +209,7 @@ lazy_static::lazy_static! {
   pub static ref JINGE_LOOP: Atom = "loop".into();
   pub static ref JINGE_KEY: Atom = "key".into();
   pub static ref JINGE_KEY_FN: Atom = "keyFn".into();
+  pub static ref JINGE_EXPECT: Atom = "expect".into();
   pub static ref JINGE_EMPTY_STR: Atom = "".into();
   pub static ref JINGE_DOUBLECLICK: Atom = "doubleclick".into();
   pub static ref JINGE_DBLCLICK: Atom = "dblclick".into();
@@ -105,23 +220,94 @@ lazy_static::lazy_static! {
     attrs.into_iter().map(|s| Atom::from(s)).collect()
   };
 
-  pub static ref JINGE_IMPORT_MODULE_ITEM: ModuleItem = gen_import_jinge();
+  /// 数值不需要单位的 css 属性（不含浏览器前缀），其它属性的数值常量会加上 px，参看 parser/attrs.rs 中的 css_number_value
+  pub static ref UNITLESS_CSS_PROPERTIES: Vec<Atom> = {
+    let mut props = vec!["animation-iteration-count", "aspect-ratio", "border-image-outset", "border-image-slice", "border-image-width", "box-flex", "box-flex-group", "box-ordinal-group", "column-count", "columns", "fill-opacity", "flex", "flex-grow", "flex-negative", "flex-order", "flex-positive", "flex-shrink", "flood-opacity", "font-weight", "grid-area", "grid-column", "grid-column-end", "grid-column-span", "grid-column-start", "grid-row", "grid-row-end", "grid-row-span", "grid-row-start", "line-clamp", "line-height", "opacity", "order", "orphans", "scale", "stop-opacity", "stroke-dasharray", "stroke-dashoffset", "stroke-miterlimit", "stroke-opacity", "stroke-width", "tab-size", "widows", "z-index", "zoom"];
+    props.sort_unstable();
+    props.into_iter().map(Atom::from).collect()
+  };
+}
+
+// 服务端渲染（SSR）使用的运行时函数，参看 codegen/ssr.rs
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_SSR_TEXT: JingeImport = x!("ssrText");
+  pub static ref JINGE_IMPORT_SSR_ATTR: JingeImport = x!("ssrAttr");
+  pub static ref JINGE_IMPORT_SSR_RENDER_COMPONENT: JingeImport = x!("ssrRenderComponent");
+  pub static ref JINGE_IMPORT_SSR_RENDER_SLOT: JingeImport = x!("ssrRenderSlot");
+  pub static ref JINGE_IMPORT_SSR_RENDER_FOR: JingeImport = x!("ssrRenderFor");
+  pub static ref JINGE_IMPORT_SSR_INTL_TEXT: JingeImport = x!("ssrIntlText");
+  pub static ref JINGE_IMPORT_SSR_INTL_RICH_TEXT: JingeImport = x!("ssrIntlRichText");
+  pub static ref JINGE_IMPORT_SSR_STYLE: JingeImport = x!("ssrStyle");
 }
 
-fn gen_import_jinge() -> ModuleItem {
-  let imports: [&'static JingeImport; 29] = [
+// hydrate 使用的运行时函数，参看 codegen/dom.rs 中的 [crate::codegen::DomCodegen::hydrate]
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_HYDRATE_CURSOR: JingeImport = x!("hydrateCursor");
+  pub static ref JINGE_IMPORT_CHILD_CURSOR: JingeImport = x!("childCursor");
+  pub static ref JINGE_IMPORT_CLAIM_ELE: JingeImport = x!("claimEle");
+  pub static ref JINGE_IMPORT_CLAIM_TEXT: JingeImport = x!("claimText");
+  pub static ref JINGE_IMPORT_HYDRATE_FC: JingeImport = x!("hydrateFunctionComponent");
+  pub static ref JINGE_IMPORT_HYDRATE_SLOT: JingeImport = x!("hydrateSlotFunction");
+  pub static ref JINGE_IMPORT_HYDRATE_INTL_TEXT: JingeImport = x!("hydrateIntlText");
+  pub static ref JINGE_IMPORT_HYDRATE_INTL_TEXT_WITH_PARAMS: JingeImport = x!("hydrateIntlTextWithParams");
+  pub static ref JINGE_IMPORT_HYDRATE_INTL_RICH_TEXT: JingeImport = x!("hydrateIntlRichText");
+}
+
+// svg 和 MathML 命名空间的元素、`xlink:href` 等命名空间属性使用的运行时函数，参看 [crate::parser::ir::Namespace]
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_CREATE_SVG_ELE: JingeImport = x!("createSvgEle");
+  pub static ref JINGE_IMPORT_CREATE_SVG_ELE_A: JingeImport = x!("createSvgEleA");
+  pub static ref JINGE_IMPORT_CREATE_MATH_ELE: JingeImport = x!("createMathEle");
+  pub static ref JINGE_IMPORT_CREATE_MATH_ELE_A: JingeImport = x!("createMathEleA");
+  pub static ref JINGE_IMPORT_SET_ATTRIBUTE_NS: JingeImport = x!("setAttributeNS");
+}
+
+// 动态组件 `<Dynamic component={...}>` 使用的运行时组件和函数，参看 [crate::parser::ir::Dynamic]
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_DYNAMIC: JingeImport = x!("Dynamic");
+  pub static ref JINGE_IMPORT_SSR_RENDER_DYNAMIC: JingeImport = x!("ssrRenderDynamic");
+  pub static ref JINGE_DYNAMIC: Atom = "Dynamic".into();
+  pub static ref JINGE_COMPONENT_ATTR: Atom = "component".into();
+}
+
+// 注册 Custom Element 使用的运行时函数，参看 codegen/custom_element.rs
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_CREATE_CUSTOM_ELEMENT: JingeImport = x!("createCustomElement");
+  pub static ref JINGE_CUSTOM_ELEMENTS: Atom = "customElements".into();
+}
+
+// 表单元素双向绑定 `bind:value={state.name}` 使用的运行时函数和标识符，参看 parser/attrs.rs
+lazy_static::lazy_static! {
+  pub static ref JINGE_IMPORT_SET_SELECT_VALUES: JingeImport = x!("setSelectValues");
+  pub static ref JINGE_BIND: Atom = "bind".into();
+  pub static ref JINGE_EVT_IDENT: JingePrivateIdent = JingePrivateIdent::new("e");
+  pub static ref JINGE_OPTION_IDENT: JingePrivateIdent = JingePrivateIdent::new("o");
+}
+
+/// 生成 `import { ... } from 'jinge'` 语句。import 的本地名称同样是私有标识符，因此需要在 [with_private_mark] 内调用。
+/// 只导入 used 中出现的运行时函数（参看 [collect_private_idents]），这样编译出的代码只依赖实际用到的运行时函数，
+/// 较旧的 jinge 运行时缺少新增的函数时，没有用到这些函数的模块仍然可以正常链接。没有用到任何运行时函数时返回 None。
+pub fn gen_import_jinge(used: &HashSet<Atom>) -> Option<ModuleItem> {
+  // Dom、hydrate、SSR 和 Custom Element 用到的全部运行时函数，按这个顺序输出。
+  let imports: [&'static JingeImport; 55] = [
     &JINGE_IMPORT_TEXT_RENDER_FN,
     &JINGE_IMPORT_RENDER_INTL_TEXT,
     &JINGE_IMPORT_RENDER_INTL_TEXT_WITH_PARAMS,
     &JINGE_IMPORT_RENDER_INTL_RICH_TEXT,
     &JINGE_IMPORT_CREATE_ELE,
     &JINGE_IMPORT_CREATE_ELE_A,
+    &JINGE_IMPORT_CREATE_SVG_ELE,
+    &JINGE_IMPORT_CREATE_SVG_ELE_A,
+    &JINGE_IMPORT_CREATE_MATH_ELE,
+    &JINGE_IMPORT_CREATE_MATH_ELE_A,
     &JINGE_IMPORT_CONTEXT,
     &JINGE_IMPORT_CREATE_TEXT_NODE,
     &JINGE_IMPORT_SET_TEXT_CONTENT,
     &JINGE_IMPORT_VM,
     &JINGE_IMPORT_ADD_EVENT,
     &JINGE_IMPORT_SET_ATTRIBUTE,
+    &JINGE_IMPORT_SET_ATTRIBUTE_NS,
+    &JINGE_IMPORT_SET_SELECT_VALUES,
     &JINGE_IMPORT_SET_REF,
     &JINGE_IMPORT_ROOT_NODES,
     &JINGE_IMPORT_SLOTS,
@@ -139,8 +325,30 @@ fn gen_import_jinge() -> ModuleItem {
     &JINGE_IMPORT_WATCH_PATH_FOR_RENDER_2,
     &JINGE_IMPORT_IF,
     &JINGE_IMPORT_FOR,
+    &JINGE_IMPORT_DYNAMIC,
+    &JINGE_IMPORT_HYDRATE_CURSOR,
+    &JINGE_IMPORT_CHILD_CURSOR,
+    &JINGE_IMPORT_CLAIM_ELE,
+    &JINGE_IMPORT_CLAIM_TEXT,
+    &JINGE_IMPORT_HYDRATE_FC,
+    &JINGE_IMPORT_HYDRATE_SLOT,
+    &JINGE_IMPORT_HYDRATE_INTL_TEXT,
+    &JINGE_IMPORT_HYDRATE_INTL_TEXT_WITH_PARAMS,
+    &JINGE_IMPORT_HYDRATE_INTL_RICH_TEXT,
+    &JINGE_IMPORT_SSR_TEXT,
+    &JINGE_IMPORT_SSR_ATTR,
+    &JINGE_IMPORT_SSR_RENDER_COMPONENT,
+    &JINGE_IMPORT_SSR_RENDER_SLOT,
+    &JINGE_IMPORT_SSR_RENDER_FOR,
+    &JINGE_IMPORT_SSR_RENDER_DYNAMIC,
+    &JINGE_IMPORT_SSR_INTL_TEXT,
+    &JINGE_IMPORT_SSR_INTL_RICH_TEXT,
+    &JINGE_IMPORT_SSR_STYLE,
+    &JINGE_IMPORT_CREATE_CUSTOM_ELEMENT,
   ];
   let specs: Vec<_> = imports
+    .into_iter()
+    .filter(|e| used.contains(&e.local))
     .map(|e| {
       ImportSpecifier::Named(ImportNamedSpecifier {
         span: DUMMY_SP,
@@ -149,20 +357,92 @@ fn gen_import_jinge() -> ModuleItem {
         is_type_only: false,
       })
     })
-    .into_iter()
     .collect();
-  ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+  if specs.is_empty() {
+    return None;
+  }
+  Some(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
     span: DUMMY_SP,
     specifiers: specs,
     src: Box::new(Str::from(JINGE.clone())),
     type_only: false,
     with: None,
     phase: ImportPhase::Evaluation,
-  }))
+  })))
+}
+
+/// 收集 node 中引用的编译器私有标识符的名称，需要在 [with_private_mark] 内调用。
+pub fn collect_private_idents<N: VisitWith<PrivateIdentCollector>>(node: &N) -> HashSet<Atom> {
+  let mut v = PrivateIdentCollector {
+    ctxt: PRIVATE_CTXT.with(|c| c.get()),
+    names: HashSet::new(),
+  };
+  node.visit_with(&mut v);
+  v.names
+}
+
+pub struct PrivateIdentCollector {
+  ctxt: SyntaxContext,
+  names: HashSet<Atom>,
+}
+impl Visit for PrivateIdentCollector {
+  noop_visit_type!();
+  fn visit_ident(&mut self, n: &Ident) {
+    if n.ctxt == self.ctxt {
+      self.names.insert(n.sym.clone());
+    }
+  }
 }
 
-pub fn emit_error(sp: Span, msg: &str) {
-  HANDLER.with(|h| {
-    h.struct_span_err(sp, msg).emit();
-  });
+#[inline]
+pub fn emit_error(sp: Span, code: DiagCode) {
+  emit_diagnostic(sp, code, &[]);
+}
+
+/// 输出诊断信息，返回最终的严重级别是否为 error。警告、被 rules 关闭或者被 `jinge-ignore` 忽略时返回 false。
+#[inline]
+pub fn emit_error_is_fatal(sp: Span, code: DiagCode) -> bool {
+  emit_diagnostic(sp, code, &[]) == Some(Severity::Error)
+}
+
+/// 输出带参数的诊断信息，参看 [DiagCode::message_with_lang]。
+#[inline]
+pub fn emit_error_with_args(sp: Span, code: DiagCode, args: &[&str]) {
+  emit_diagnostic(sp, code, args);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::RenderMode;
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_import_used_helpers() {
+    let src = "export function A(props) {
+  return <div class={props.c}>{props.t}</div>;
+}";
+    let import_line = |render_mode| {
+      let TransformOutput { code, .. } = inner_transform(
+        "test.tsx".into(),
+        2,
+        src.into(),
+        TransformOptions {
+          render_mode,
+          ..Default::default()
+        },
+      );
+      code.lines().next().unwrap().to_string()
+    };
+    // 只导入生成的代码实际用到的运行时函数
+    let dom = import_line(RenderMode::Dom);
+    assert!(dom.contains("setAttribute as setAttribute$jg$"));
+    assert!(dom.contains("watchPathForRender as watchPathForRender$jg$"));
+    assert!(!dom.contains("setSelectValues"));
+    assert!(!dom.contains("Dynamic"));
+    assert!(!dom.contains("createCustomElement"));
+    assert_eq!(
+      import_line(RenderMode::Ssr),
+      r#"import { ssrText as ssrText$jg$, ssrAttr as ssrAttr$jg$ } from "jinge";"#
+    );
+  }
 }
@@ -0,0 +1,591 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use swc_common::{
+  comments::SingleThreadedComments,
+  errors::{DiagnosticBuilder, DiagnosticId, Emitter, Level, HANDLER},
+  sync::Lrc,
+  BytePos, SourceFile, SourceMap, Span,
+};
+
+/// 诊断信息的语言。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+  #[default]
+  Zh,
+  En,
+}
+impl Lang {
+  /// 环境变量 `JINGE_COMPILER_LANG`，在编译选项未指定语言时使用。
+  pub const ENV_NAME: &'static str = "JINGE_COMPILER_LANG";
+
+  pub fn parse(s: &str) -> Option<Self> {
+    let s = s.trim().to_ascii_lowercase();
+    if s.starts_with("zh") {
+      Some(Lang::Zh)
+    } else if s.starts_with("en") {
+      Some(Lang::En)
+    } else {
+      None
+    }
+  }
+  /// 优先使用编译选项指定的语言，其次是环境变量 `JINGE_COMPILER_LANG`，默认为中文。
+  pub fn resolve(option: Option<&str>) -> Self {
+    option
+      .and_then(Self::parse)
+      .or_else(|| {
+        std::env::var(Self::ENV_NAME)
+          .ok()
+          .and_then(|v| Self::parse(&v))
+      })
+      .unwrap_or(Lang::Zh)
+  }
+}
+
+/// 诊断的严重级别。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+  Error,
+  Warning,
+  Hint,
+}
+impl Severity {
+  /// 解析配置中的严重级别，`off` 表示关闭该诊断，返回 `Some(None)`。
+  pub fn parse(s: &str) -> Option<Option<Self>> {
+    match s {
+      "error" => Some(Some(Severity::Error)),
+      "warning" | "warn" => Some(Some(Severity::Warning)),
+      "hint" => Some(Some(Severity::Hint)),
+      "off" => Some(None),
+      _ => None,
+    }
+  }
+  fn level(self) -> Level {
+    match self {
+      Severity::Error => Level::Error,
+      Severity::Warning => Level::Warning,
+      Severity::Hint => Level::Help,
+    }
+  }
+}
+
+/// 行内的 `// jinge-ignore JG1002 JG2103` 注释，忽略注释所在行以及下一行的指定诊断。
+/// 不指定编号时忽略这两行的全部诊断。
+struct IgnoreComment {
+  lo: BytePos,
+  hi: BytePos,
+  codes: Vec<String>,
+}
+
+const IGNORE_COMMENT_PREFIX: &str = "jinge-ignore";
+
+/// 诊断输出策略：语言、严重级别调整以及忽略规则。
+#[derive(Default)]
+pub struct DiagnosticPolicy {
+  pub lang: Lang,
+  /// 将全部 warning 提升为 error，通常用于 CI。
+  pub warnings_as_errors: bool,
+  /// 按诊断编号调整严重级别，None 表示关闭该诊断。
+  pub rules: HashMap<String, Option<Severity>>,
+  ignores: Vec<IgnoreComment>,
+}
+impl DiagnosticPolicy {
+  pub fn new(lang: Lang) -> Self {
+    Self {
+      lang,
+      ..Default::default()
+    }
+  }
+
+  /// 收集源码中的 `jinge-ignore` 注释。
+  pub fn collect_ignores(&mut self, fm: &SourceFile, comments: &SingleThreadedComments) {
+    let (leading, trailing) = comments.borrow_all();
+    leading
+      .values()
+      .chain(trailing.values())
+      .flatten()
+      .for_each(|c| {
+        let Some(rest) = c.text.trim().strip_prefix(IGNORE_COMMENT_PREFIX) else {
+          return;
+        };
+        let Some(line) = fm.lookup_line(c.span.lo) else {
+          return;
+        };
+        let (lo, _) = fm.line_bounds(line);
+        let (_, hi) = fm.line_bounds((line + 1).min(fm.count_lines() - 1));
+        self.ignores.push(IgnoreComment {
+          lo,
+          hi,
+          codes: rest.split_whitespace().map(|c| c.to_string()).collect(),
+        });
+      });
+  }
+
+  /// 计算诊断在 sp 位置最终的严重级别，返回 None 表示该诊断被忽略。
+  fn severity_of(&self, sp: Span, code: DiagCode) -> Option<Severity> {
+    if self.ignores.iter().any(|ig| {
+      sp.lo >= ig.lo
+        && sp.lo < ig.hi
+        && (ig.codes.is_empty() || ig.codes.iter().any(|c| c == code.code()))
+    }) {
+      return None;
+    }
+    let severity = match self.rules.get(code.code()) {
+      Some(rule) => (*rule)?,
+      None => code.severity(),
+    };
+    if self.warnings_as_errors && severity == Severity::Warning {
+      Some(Severity::Error)
+    } else {
+      Some(severity)
+    }
+  }
+}
+
+thread_local! {
+  static POLICY: RefCell<Rc<DiagnosticPolicy>> = RefCell::new(Default::default());
+}
+
+/// 在 f 的执行期间，使用 policy 输出诊断信息。
+pub fn with_policy<R>(policy: Rc<DiagnosticPolicy>, f: impl FnOnce() -> R) -> R {
+  let prev = POLICY.with(|p| p.replace(policy));
+  let r = f();
+  POLICY.with(|p| p.replace(prev));
+  r
+}
+
+/// 按当前的 [DiagnosticPolicy] 输出诊断信息，返回最终的严重级别，None 表示该诊断被关闭或忽略。
+pub fn emit_diagnostic(sp: Span, code: DiagCode, args: &[&str]) -> Option<Severity> {
+  let policy = POLICY.with(|p| p.borrow().clone());
+  let severity = policy.severity_of(sp, code)?;
+  let msg = code.message_with_lang(policy.lang, args);
+  HANDLER.with(|h| {
+    DiagnosticBuilder::new_with_code(
+      h,
+      severity.level(),
+      Some(DiagnosticId::Error(code.code().to_string())),
+      &msg,
+    )
+    .set_span(sp)
+    .emit();
+  });
+  Some(severity)
+}
+
+macro_rules! diagnostics {
+  ($( $name:ident = $code:literal $severity:ident { zh: $zh:literal, en: $en:literal } )*) => {
+    /// 编译器的全部诊断信息。每个诊断都有稳定的编号（例如 `JG2104`），编号一旦发布就不再变更含义。
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum DiagCode {
+      $( $name, )*
+    }
+    impl DiagCode {
+      pub fn code(self) -> &'static str {
+        match self {
+          $( Self::$name => $code, )*
+        }
+      }
+      /// 诊断的默认严重级别，可以通过 [DiagnosticPolicy] 调整。
+      pub fn severity(self) -> Severity {
+        match self {
+          $( Self::$name => Severity::$severity, )*
+        }
+      }
+      fn template(self, lang: Lang) -> &'static str {
+        match (self, lang) {
+          $(
+            (Self::$name, Lang::Zh) => $zh,
+            (Self::$name, Lang::En) => $en,
+          )*
+        }
+      }
+    }
+  };
+}
+
+diagnostics! {
+  // JG1xxx：组件识别
+  PropsDestructure = "JG1001" Error {
+    zh: "函数组件的 props 参数只支持标识符或对象解构写法",
+    en: "The props parameter of a function component must be an identifier or an object pattern"
+  }
+  ComponentNameHmr = "JG1002" Warning {
+    zh: "非常规命令的函数组件无法使用 HMR",
+    en: "A function component that is not bound to a plain identifier cannot use HMR"
+  }
+  // JG1003 曾用于匿名函数组件的 HMR 警告，匿名组件支持编译后已废弃，不再复用该编号。
+  CustomElementName = "JG1004" Error {
+    zh: "Custom Element 名称 {0} 不合法，必须以小写字母打头并且包含 -，例如 x-card",
+    en: "Invalid custom element name `{0}`: it must start with a lowercase letter and contain a hyphen, e.g. x-card"
+  }
+  CustomElementTarget = "JG1005" Warning {
+    zh: "jinge-custom-element 注释只能用于模块顶层的函数组件",
+    en: "The jinge-custom-element comment can only be used on a top-level function component"
+  }
+  PropsAssign = "JG1006" Error {
+    zh: "不能给解构的 props 属性 {0} 赋值，props 是只读的",
+    en: "Cannot assign to `{0}`: destructured props are read-only"
+  }
+
+  // JG2xxx：模板与 JSX
+  JsxElementName = "JG2001" Error {
+    zh: "未知的 JSX 格式，opening.name 未找到",
+    en: "Unknown JSX format: unsupported element name"
+  }
+  InvalidTag = "JG2002" Error {
+    zh: "不支持的 Tag。合法 Tag 为：大写字母打头为 Component 组件，小写字母打头为 html 元素。",
+    en: "Unsupported tag. Tags starting with an uppercase letter are components, tags starting with a lowercase letter are html elements."
+  }
+  UnsupportedJsx = "JG2003" Error {
+    zh: "不支持的 jsx 格式",
+    en: "Unsupported JSX syntax"
+  }
+  FunctionInTemplate = "JG2004" Error {
+    zh: "tsx 中不支持函数，如果是定义 Slot 请使用箭头函数",
+    en: "Functions are not supported in templates. Use an arrow function to define a slot."
+  }
+  ArrayInTemplate = "JG2005" Error {
+    zh: "tsx 中不能直接使用数组表达式",
+    en: "Array expressions cannot be used directly in templates"
+  }
+  JsxLocalUsage = "JG2006" Error {
+    zh: "局部变量 {0} 的值是 jsx，只能在模板中作为子节点使用",
+    en: "Local variable `{0}` holds JSX and can only be used as a child in the template"
+  }
+  JsxLocalNotConst = "JG2007" Error {
+    zh: "jsx 只能赋值给 const 声明的局部变量",
+    en: "JSX can only be assigned to a `const` local variable"
+  }
+  JsxInClassMethod = "JG2008" Error {
+    zh: "jsx 未被编译：类方法中的 jsx 不会被编译",
+    en: "JSX was not compiled: JSX inside class methods is not compiled"
+  }
+  JsxInCallback = "JG2009" Error {
+    zh: "jsx 未被编译：作为回调参数的函数不是组件，其中的 jsx 不会被编译。请将其提取为模块顶层的函数组件",
+    en: "JSX was not compiled: a function passed as a callback argument is not a component. Extract it into a top-level function component."
+  }
+  JsxInNestedFunction = "JG2010" Error {
+    zh: "jsx 未被编译：嵌套函数不是组件，只有模块顶层的函数组件会被编译。请将其提取为模块顶层的函数组件",
+    en: "JSX was not compiled: nested functions are not components, only top-level function components are compiled. Extract it into a top-level function component."
+  }
+  JsxNotCompiled = "JG2011" Error {
+    zh: "jsx 未被编译：jsx 只能出现在函数组件的 return 语句或者 const 局部变量中",
+    en: "JSX was not compiled: JSX may only appear in the return statement or const locals of a function component"
+  }
+  JsxLocalCaptureAssign = "JG2012" Error {
+    zh: "jsx 局部变量 {0} 引用的变量 {1} 在声明之后被重新赋值。jsx 会内联到模板中使用它的位置，赋值会改变渲染的内容",
+    en: "JSX local `{0}` uses `{1}`, which is reassigned after the declaration. The JSX is inlined where it is used, so the assignment would change what renders"
+  }
+  JsxLocalSideEffect = "JG2013" Warning {
+    zh: "jsx 局部变量 {0} 中有函数调用等有副作用的表达式，它在模板中被使用多次，每一处都会重新执行这些表达式",
+    en: "JSX local `{0}` contains function calls or other side effects and is used more than once in the template; they run again at each use"
+  }
+
+  // JG21xx：属性
+  SpreadNotIdent = "JG2101" Error {
+    zh: "解构写法...后必须是 Ident",
+    en: "The expression after ... must be an identifier"
+  }
+  SpreadOnlyOnce = "JG2102" Error {
+    zh: "解构写法透传属性只能出现一次",
+    en: "Spread props can only be used once"
+  }
+  ChildrenAttr = "JG2103" Warning {
+    zh: "不能使用 children 属性名，如果是定义  Slot，请使用 jsx 子元素的方式定义！",
+    en: "`children` cannot be used as an attribute name. Define slots with JSX children instead."
+  }
+  DuplicateRef = "JG2104" Error {
+    zh: "不能重复指定 ref",
+    en: "`ref` cannot be specified more than once"
+  }
+  InvalidRef = "JG2105" Error {
+    zh: "ref 属性值不合法",
+    en: "Invalid `ref` attribute value"
+  }
+  EventNotArrow = "JG2106" Error {
+    zh: "事件属性的属性值必须是箭头函数",
+    en: "The value of an event attribute must be an arrow function"
+  }
+  JsxAttrValue = "JG2107" Error {
+    zh: "不支持 JSX 元素作为属性值",
+    en: "JSX elements cannot be used as attribute values"
+  }
+  FnAttrValue = "JG2108" Error {
+    zh: "不支持函数作为属性值。如果是想传递事件，请使用 on 打头的属性名，例如 onClick",
+    en: "Functions cannot be used as attribute values. To pass an event handler, use an attribute name starting with `on`, e.g. onClick"
+  }
+  UnsupportedAttrValue = "JG2109" Error {
+    zh: "不支持该类型的属性值。",
+    en: "Unsupported attribute value type."
+  }
+  DynamicComponentMissing = "JG2110" Error {
+    zh: "<Dynamic> 必须通过 component 属性指定渲染的组件或 html 标签",
+    en: "<Dynamic> requires a `component` attribute naming the component or html tag to render"
+  }
+  InvalidBinding = "JG2111" Error {
+    zh: "不支持 bind:{0}。bind:value 只能用于 input、textarea 和 select，bind:checked 只能用于 checkbox 和 radio 类型的 input",
+    en: "`bind:{0}` is not supported here. bind:value works on input, textarea and select; bind:checked works on checkbox and radio inputs"
+  }
+  BindingTarget = "JG2112" Error {
+    zh: "bind 属性的值必须是可以监听和赋值的成员表达式，例如 {state.name}",
+    en: "The value of a bind attribute must be a watchable, assignable member expression, e.g. {state.name}"
+  }
+  ClassBindWithToggles = "JG2113" Error {
+    zh: "需要监听变更的 class 属性每次更新都会覆盖整个 class，不能和 class:name、数组或对象写法的 class 一起使用",
+    en: "A watched `class` attribute overwrites the whole class on every update and cannot be combined with class:name or array/object class values"
+  }
+  ComponentNamespacedAttr = "JG2114" Error {
+    zh: "组件不支持带命名空间前缀的属性 {0}，命名空间属性只能用于 html、svg 和 MathML 元素。组件的 props 请使用普通的属性名",
+    en: "Components do not support the namespaced attribute `{0}`; namespaced attributes only apply to html, svg and MathML elements. Use a plain prop name instead"
+  }
+  UnsupportedStyleProp = "JG2115" Error {
+    zh: "style 对象中每个 css 属性都要在编译时确定，不支持展开（...）、计算属性名和方法。需要合并样式时请写出每个属性，或者使用字符串形式的 style",
+    en: "Every css property of a style object must be known at compile time; spreads (...), computed keys and methods are not supported. List each property, or use a string style to merge styles"
+  }
+
+  // JG3xxx：表达式监听
+  CallAsMemberObject = "JG3001" Error {
+    zh: "暂不支持该 Call 表达式作为 Member Expr 的 object",
+    en: "A call expression is not yet supported as the object of a member expression"
+  }
+  UnsupportedExpr = "JG3002" Error {
+    zh: "不支持该类型的表达式",
+    en: "Unsupported expression type"
+  }
+  UnsupportedLitProp = "JG3003" Error {
+    zh: "不支持该常量作为属性",
+    en: "This literal cannot be used as a property"
+  }
+
+  // JG4xxx：Slot
+  SlotMaxDepth = "JG4001" Error {
+    zh: "Slot 渲染最多支持 2 层，比如 props.children 或 props.children.x",
+    en: "Slot rendering supports at most 2 levels, e.g. props.children or props.children.x"
+  }
+  SlotRenderArgsCount = "JG4002" Warning {
+    zh: "slot 渲染函数的只允许一个参数，该参数应该是具备双向绑定属性的 ViewModel。是否忘了使用 object 包裹这几个参数？",
+    en: "A slot render function accepts a single argument, which should be a reactive ViewModel. Did you forget to wrap the arguments in an object?"
+  }
+  SlotRenderArgSpread = "JG4003" Error {
+    zh: "Slot 渲染函数的参数不支持 ... 解构数组的写法。",
+    en: "The argument of a slot render function cannot be a ... spread."
+  }
+  SlotRenderArgIdent = "JG4004" Error {
+    zh: "Slot 渲染参数应该是具备双向绑定属性的 ViewModel。是否忘了使用 object 包裹 {0}？如果就是想透传该 ViewModel 作为 Slot 参数，可使用 {...{0}} 的写法。",
+    en: "The slot render argument should be a reactive ViewModel. Did you forget to wrap {0} in an object? To pass the ViewModel through as the slot argument, write {...{0}}."
+  }
+  SlotRenderArgNotObject = "JG4005" Error {
+    zh: "Slot 渲染参数必须是 key-value 类型的 Object。",
+    en: "The slot render argument must be a key-value object literal."
+  }
+  SlotOutsideComponent = "JG4006" Error {
+    zh: "Slot 定义必须位于组件下",
+    en: "Slots can only be defined as children of a component"
+  }
+  SlotArrowBody = "JG4007" Error {
+    zh: "使用箭头函数定义默认 Slot 时必须直接在箭头后返回值",
+    en: "An arrow function defining the default slot must return its value directly after the arrow"
+  }
+  SlotParamDestructure = "JG4008" Warning {
+    zh: "slot 函数的参数不要使用解构的写法，会导致数据的绑定失效。",
+    en: "Do not destructure slot function parameters, it breaks data binding."
+  }
+  SlotDefSpread = "JG4009" Error {
+    zh: "Slot 定义不支持 ... 的书写方式",
+    en: "Slot definitions do not support ... spread"
+  }
+  SlotNameNotConst = "JG4010" Error {
+    zh: "Slot 定义的名称必须是常量字符串",
+    en: "Slot names must be constant strings"
+  }
+  SlotDefNotKeyValue = "JG4011" Error {
+    zh: "Slot 定义必须是 Key: Value 的形式",
+    en: "Slot definitions must be in the form Key: Value"
+  }
+
+  // JG5xxx：map 循环
+  MapKeyNoParams = "JG5001" Error {
+    zh: "map 函数没有指定参数，因此 key 属性无法转换为 <For> 组件的 keyFn 参数。",
+    en: "The map callback has no parameters, so the key attribute cannot be converted to the keyFn of <For>."
+  }
+  MapBadKey = "JG5002" Warning {
+    zh: "key 的表达式必须是 map 函数的参数或参数的属性表达式",
+    en: "The key expression must be a map callback parameter or a property of one"
+  }
+  MapFnCallback = "JG5003" Warning {
+    zh: "map 函数的参数请使用箭头函数！",
+    en: "Use an arrow function as the map callback!"
+  }
+
+  // JG6xxx：国际化
+  IntlTextNotLiteral = "JG6001" Error {
+    zh: "t 函数的第一个参数必须是字符串常量，代表默认文本",
+    en: "The first argument of t must be a string literal with the default text"
+  }
+  IntlOptionsSpread = "JG6002" Error {
+    zh: "t 函数的 options 参数不支持 ... 解构写法",
+    en: "The options argument of t cannot use ... spread"
+  }
+  IntlParamsSpread = "JG6003" Error {
+    zh: "t 函数的 params 参数不支持 ... 解构写法",
+    en: "The params argument of t cannot use ... spread"
+  }
+  IntlParamsNotObject = "JG6004" Error {
+    zh: "t 函数的 params 参数必须是 object 类型",
+    en: "The params argument of t must be an object"
+  }
+  IntlParamsNotKeyValue = "JG6005" Error {
+    zh: "t 函数的 params 参数必须是 key-value 类型的 Object。",
+    en: "The params argument of t must be a key-value object literal."
+  }
+}
+
+impl DiagCode {
+  /// 按 lang 语言生成诊断信息，`{0}`、`{1}` 等占位符依次替换为 args 中的参数。
+  pub fn message_with_lang(self, lang: Lang, args: &[&str]) -> String {
+    let mut msg = self.template(lang).to_string();
+    args.iter().enumerate().for_each(|(i, arg)| {
+      msg = msg.replace(&format!("{{{}}}", i), arg);
+    });
+    msg
+  }
+
+  /// 按当前 [DiagnosticPolicy] 的语言生成诊断信息。
+  pub fn message(self, args: &[&str]) -> String {
+    let lang = POLICY.with(|p| p.borrow().lang);
+    self.message_with_lang(lang, args)
+  }
+}
+
+/// 结构化的诊断信息。行号和列号都从 1 开始。
+#[derive(Debug, Clone)]
+pub struct CompileDiagnostic {
+  pub code: Option<String>,
+  pub level: &'static str,
+  pub message: String,
+  pub line: usize,
+  pub column: usize,
+  pub end_line: usize,
+  pub end_column: usize,
+}
+
+/// 把诊断信息结构化地收集起来，同时转发给 inner 输出（比如输出到 stderr）。
+pub struct CollectEmitter {
+  pub inner: Option<Box<dyn Emitter>>,
+  pub cm: Lrc<SourceMap>,
+  pub diagnostics: Arc<Mutex<Vec<CompileDiagnostic>>>,
+}
+impl Emitter for CollectEmitter {
+  fn emit(&mut self, db: &DiagnosticBuilder<'_>) {
+    if let Some(inner) = &mut self.inner {
+      inner.emit(db);
+    }
+
+    let (line, column, end_line, end_column) = match db.span.primary_span() {
+      Some(sp) if !sp.is_dummy() => {
+        let lo = self.cm.lookup_char_pos(sp.lo);
+        let hi = self.cm.lookup_char_pos(sp.hi);
+        (lo.line, lo.col.0 + 1, hi.line, hi.col.0 + 1)
+      }
+      _ => (0, 0, 0, 0),
+    };
+    self.diagnostics.lock().unwrap().push(CompileDiagnostic {
+      code: db.code.as_ref().map(|c| match c {
+        swc_common::errors::DiagnosticId::Error(c) | swc_common::errors::DiagnosticId::Lint(c) => {
+          c.clone()
+        }
+      }),
+      level: match db.level {
+        Level::Warning => "warning",
+        Level::Note | Level::Help => "hint",
+        _ => "error",
+      },
+      message: db.message(),
+      line,
+      column,
+      end_line,
+      end_column,
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_diagnostic_code() {
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A() {
+  return <div ref={a} ref={b}></div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code.as_deref(), Some("JG2104"));
+    assert_eq!(
+      diagnostics[0].message,
+      "`ref` cannot be specified more than once"
+    );
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 23));
+  }
+
+  #[test]
+  fn test_diagnostic_policy() {
+    let code = "export function A() {
+  return <div children={1}></div>;
+}
+export function B() {
+  // jinge-ignore JG2103
+  return <div children={1}></div>;
+}";
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      code.into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code.as_deref(), Some("JG2103"));
+    assert_eq!(diagnostics[0].level, "warning");
+    assert_eq!(diagnostics[0].line, 2);
+
+    let mut diagnostic = DiagnosticPolicy::new(Lang::En);
+    diagnostic.warnings_as_errors = true;
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      code.into(),
+      TransformOptions {
+        diagnostic,
+        ..Default::default()
+      },
+    );
+    assert_eq!(diagnostics[0].level, "error");
+
+    let mut diagnostic = DiagnosticPolicy::new(Lang::En);
+    diagnostic.rules.insert("JG2103".into(), None);
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      code.into(),
+      TransformOptions {
+        diagnostic,
+        ..Default::default()
+      },
+    );
+    assert!(diagnostics.is_empty());
+  }
+}
@@ -1,29 +1,41 @@
 mod ast;
+mod codegen;
 mod common;
+mod diagnostic;
 mod parser;
 mod visitor;
 
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
-use common::IntlType;
+use common::{with_private_mark, IntlType, RenderMode, SourceLocator};
+use diagnostic::{
+  with_policy, CollectEmitter, CompileDiagnostic, DiagnosticPolicy, Lang, Severity,
+};
 use neon::prelude::*;
 
+use parser::custom_element::CustomElementPragmas;
 use swc_common::input::SourceFileInput;
 use swc_common::{
   collections::AHashMap,
-  errors::{ColorConfig, Handler, HANDLER},
+  comments::SingleThreadedComments,
+  errors::{emitter::EmitterWriter, ColorConfig, Handler, HANDLER},
   source_map::SourceMapGenConfig,
   sync::Lrc,
-  BytePos, FileName, Globals, Mark, SourceMap, GLOBALS,
+  BytePos, FileName, Globals, Mark, SourceFile, SourceMap, GLOBALS,
 };
-use swc_core::ecma::ast::{EsVersion, Ident, IdentName};
+use swc_core::ecma::ast::{EsVersion, Ident, IdentName, Program};
 use swc_ecma_codegen::{text_writer::JsWriter, Emitter, Node};
 use swc_ecma_parser::{lexer::Lexer, Parser, Syntax, TsSyntax};
-use swc_ecma_transforms_base::fixer::fixer;
+use swc_ecma_transforms_base::{
+  fixer::fixer,
+  hygiene::{hygiene_with_config, Config as HygieneConfig},
+  resolver,
+};
 use swc_ecma_transforms_typescript::strip;
 use swc_ecma_visit::{as_folder, noop_visit_type, FoldWith, Visit, VisitWith};
-use visitor::{IntlTransformVisitor, TemplateTransformVisitor};
+use visitor::{IntlTransformVisitor, LeftoverJsxVisitor, TemplateTransformVisitor};
 
 struct SourceMapConfig<'a> {
   filename: &'a str,
@@ -110,67 +122,219 @@ fn print(
   (src, map)
 }
 
-///
-/// intl_type 国际化类型： 0： 不启用国际化，1： 启用国际化，保留原始文本，2：启用国际化，去除原始文本。
-fn inner_transform(
-  filename: String,
+#[derive(Default)]
+pub struct TransformOptions {
+  pub sourcemap_enabled: bool,
+  pub intl_type: IntlType,
+  /// 诊断信息的语言、严重级别和忽略规则
+  pub diagnostic: DiagnosticPolicy,
+  /// 高阶函数组件的包装函数白名单，参看 [TemplateTransformVisitor]
+  pub hoc_wrappers: Vec<String>,
+  /// 模板的编译目标，默认编译为浏览器 DOM 渲染
+  pub render_mode: RenderMode,
+  /// 开发模式。目前只影响 hydrate：生成的代码带上模板节点的源码位置，用于报告和服务端渲染的 DOM 不匹配的位置
+  pub dev: bool,
+}
+
+pub struct TransformOutput {
+  pub code: String,
+  pub parsed_components: String,
+  pub map: Option<String>,
+  pub diagnostics: Vec<CompileDiagnostic>,
+}
+
+#[derive(Default)]
+pub struct AnalyzeOptions {
+  pub intl_type: IntlType,
+  pub diagnostic: DiagnosticPolicy,
+  pub hoc_wrappers: Vec<String>,
+}
+
+pub struct AnalyzeOutput {
+  pub parsed_components: Vec<String>,
+  pub diagnostics: Vec<CompileDiagnostic>,
+}
+
+/// 创建收集诊断信息的 Handler，echo 为 true 时同时把诊断信息输出到 stderr。
+fn create_handler(
+  cm: Lrc<SourceMap>,
+  diagnostics: Arc<Mutex<Vec<CompileDiagnostic>>>,
+  echo: bool,
+) -> Handler {
+  Handler::with_emitter(
+    true,
+    false,
+    Box::new(CollectEmitter {
+      inner: if echo {
+        Some(Box::new(EmitterWriter::stderr(
+          ColorConfig::Auto,
+          Some(cm.clone()),
+          false,
+          false,
+        )))
+      } else {
+        None
+      },
+      cm,
+      diagnostics,
+    }),
+  )
+}
+
+/// transform 和 analyze 共用的编译配置。
+struct CompileConfig<'a> {
+  /// 1： ts 文件，2： tsx 文件
   code_type: usize,
-  code: String,
-  sourcemap_enabled: bool,
   intl_type: IntlType,
-) -> (String, String, Option<String>) {
-  // let code = Lrc::new(code);
-  let cm: Arc<SourceMap> = Arc::<SourceMap>::default();
-  let fm = cm.new_source_file(Arc::new(FileName::from(PathBuf::from(&filename))), code);
-  let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
-  // HANDLER.set(t, f)
+  hoc_wrappers: &'a [String],
+  render_mode: RenderMode,
+  dev: bool,
+  /// 只检查不生成代码，参看 [TemplateTransformVisitor::check_only]
+  check_only: bool,
+}
+
+/// 解析源码并执行模板和国际化转换，transform 和 analyze 共用这部分流程。
+/// finish 在同一个 GLOBALS/HANDLER 作用域内处理转换后的 module，参数是 module 和 top_level_mark。
+/// 存在语法错误时返回 None。
+fn compile_module<R>(
+  fm: &Lrc<SourceFile>,
+  handler: &Handler,
+  config: CompileConfig,
+  mut policy: DiagnosticPolicy,
+  parsed_components: &mut Vec<String>,
+  finish: impl FnOnce(Program, Mark) -> R,
+) -> Option<R> {
+  let comments = SingleThreadedComments::default();
+  // 只有 tsx 文件才按 tsx 语法解析，ts 文件中的 `<T>expr` 类型断言不能被当作 jsx
+  let tsx = config.code_type == 2;
   let lexer = Lexer::new(
     Syntax::Typescript(TsSyntax {
-      tsx: true,
+      tsx,
       ..Default::default()
     }),
     EsVersion::latest(),
-    SourceFileInput::from(&*fm),
-    None,
+    SourceFileInput::from(fm.as_ref()),
+    Some(&comments),
   );
 
   let mut parser = Parser::new_from(lexer);
 
   for e in parser.take_errors() {
-    e.into_diagnostic(&handler).emit();
+    e.into_diagnostic(handler).emit();
   }
 
-  let module = parser
-    .parse_program()
-    .map_err(|e| e.into_diagnostic(&handler).emit())
-    .expect("failed to parse module.");
+  let module = match parser.parse_program() {
+    Ok(module) => module,
+    Err(e) => {
+      e.into_diagnostic(handler).emit();
+      return None;
+    }
+  };
+  policy.collect_ignores(fm, &comments);
+  let custom_element_pragmas = CustomElementPragmas::collect(&comments);
+  let policy = Rc::new(policy);
+  let CompileConfig {
+    intl_type,
+    hoc_wrappers,
+    render_mode,
+    dev,
+    check_only,
+    ..
+  } = config;
+  let locator = if dev && render_mode == RenderMode::Hydrate {
+    Some(SourceLocator::new(fm.clone()))
+  } else {
+    None
+  };
 
   GLOBALS.set(&Globals::default(), || {
     let unresolved_mark = Mark::new();
     let top_level_mark = Mark::new();
+    // 编译器生成的局部变量使用的私有 Mark，参看 common::with_private_mark
+    let private_mark = Mark::new();
 
+    let module = module.fold_with(&mut resolver(unresolved_mark, top_level_mark, true));
     // Remove typescript types
     let module = module.fold_with(&mut strip(unresolved_mark, top_level_mark));
 
-    HANDLER.set(&handler, move || {
-      let mut parsed_components: Vec<String> = vec![];
+    HANDLER.set(handler, || {
+      let module = with_policy(policy, || {
+        let module = if tsx {
+          // 只有 tsx 类型才需要转换
+          let module = with_private_mark(private_mark, unresolved_mark, || {
+            let t = TemplateTransformVisitor::new(
+              parsed_components,
+              intl_type,
+              hoc_wrappers,
+              render_mode,
+              locator,
+              custom_element_pragmas,
+            )
+            .check_only(check_only);
+            module.fold_with(&mut as_folder(t))
+          });
+          // 检查未被编译的 jsx
+          module.visit_with(&mut LeftoverJsxVisitor::default());
+          module
+        } else {
+          // Ensure that we have enough parenthesis.
+          module
+        };
 
-      let module = if code_type == 2 {
-        // 只有 tsx 类型才需要转换
-        let t = TemplateTransformVisitor::new(&mut parsed_components, intl_type);
-        module.fold_with(&mut as_folder(t))
-      } else {
-        // Ensure that we have enough parenthesis.
-        module
-      };
+        if let IntlType::Enabled(drop_default_text) = intl_type {
+          let t = IntlTransformVisitor::new(drop_default_text);
+          module.fold_with(&mut as_folder(t))
+        } else {
+          module
+        }
+      });
+      Some(finish(module, top_level_mark))
+    })
+  })
+}
 
-      let module = if let IntlType::Enabled(drop_default_text) = intl_type {
-        let t = IntlTransformVisitor::new(drop_default_text);
-        module.fold_with(&mut as_folder(t))
-      } else {
-        module
-      };
+///
+/// intl_type 国际化类型： 0： 不启用国际化，1： 启用国际化，保留原始文本，2：启用国际化，去除原始文本。
+pub fn inner_transform(
+  filename: String,
+  code_type: usize,
+  code: String,
+  options: TransformOptions,
+) -> TransformOutput {
+  let TransformOptions {
+    sourcemap_enabled,
+    intl_type,
+    diagnostic,
+    hoc_wrappers,
+    render_mode,
+    dev,
+  } = options;
+  // let code = Lrc::new(code);
+  let cm: Arc<SourceMap> = Arc::<SourceMap>::default();
+  let fm = cm.new_source_file(Arc::new(FileName::from(PathBuf::from(&filename))), code);
+  let diagnostics = Arc::new(Mutex::new(vec![]));
+  let handler = create_handler(cm.clone(), diagnostics.clone(), true);
+  let mut parsed_components: Vec<String> = vec![];
 
+  let output = compile_module(
+    &fm,
+    &handler,
+    CompileConfig {
+      code_type,
+      intl_type,
+      hoc_wrappers: &hoc_wrappers,
+      render_mode,
+      dev,
+      check_only: false,
+    },
+    diagnostic,
+    &mut parsed_components,
+    |module, top_level_mark| {
+      // 生成的代码和用户代码的同名变量，通过 hygiene 重命名保证不会互相遮蔽。
+      let module = module.fold_with(&mut hygiene_with_config(HygieneConfig {
+        top_level_mark,
+        ..Default::default()
+      }));
       let module = module.fold_with(&mut fixer(None));
 
       let source_map_names = if sourcemap_enabled {
@@ -184,11 +348,136 @@ fn inner_transform(
       } else {
         Default::default()
       };
-      let (code, map) = print(&filename, cm, &module, sourcemap_enabled, &source_map_names);
+      print(&filename, cm, &module, sourcemap_enabled, &source_map_names)
+    },
+  );
+  // 语法错误无法继续编译，原样返回源码，错误通过 diagnostics 报告。
+  let (code, map) = output.unwrap_or_else(|| (fm.src.to_string(), None));
+  let diagnostics = std::mem::take(&mut *diagnostics.lock().unwrap());
+  TransformOutput {
+    code,
+    parsed_components: parsed_components.join(","),
+    map,
+    diagnostics,
+  }
+}
 
-      (code, parsed_components.join(","), map)
-    })
-  })
+/// 只检查不生成代码，返回诊断信息和组件信息，供编辑器插件、lint 等工具使用。
+/// 模板只解析到 IR，不会执行渲染代码生成、hygiene 和 source map，诊断信息也不会输出到 stderr。
+/// 国际化转换仍然执行，t 函数参数的诊断信息在这一步报告，它只改写 t 函数的调用参数，开销很小。
+/// code_type 和 [inner_transform] 相同，只有 tsx 文件才会检查模板。
+pub fn inner_analyze(
+  filename: String,
+  code_type: usize,
+  code: String,
+  options: AnalyzeOptions,
+) -> AnalyzeOutput {
+  let cm: Arc<SourceMap> = Arc::<SourceMap>::default();
+  let fm = cm.new_source_file(Arc::new(FileName::from(PathBuf::from(&filename))), code);
+  let diagnostics = Arc::new(Mutex::new(vec![]));
+  let handler = create_handler(cm, diagnostics.clone(), false);
+  let mut parsed_components: Vec<String> = vec![];
+  compile_module(
+    &fm,
+    &handler,
+    CompileConfig {
+      code_type,
+      intl_type: options.intl_type,
+      hoc_wrappers: &options.hoc_wrappers,
+      // 诊断信息和编译目标无关
+      render_mode: RenderMode::Dom,
+      dev: false,
+      check_only: true,
+    },
+    options.diagnostic,
+    &mut parsed_components,
+    |_, _| (),
+  );
+  let diagnostics = std::mem::take(&mut *diagnostics.lock().unwrap());
+  AnalyzeOutput {
+    parsed_components,
+    diagnostics,
+  }
+}
+
+fn diagnostics_to_js<'a>(
+  cx: &mut FunctionContext<'a>,
+  diagnostics: Vec<CompileDiagnostic>,
+) -> JsResult<'a, JsArray> {
+  let arr = cx.empty_array();
+  for (i, d) in diagnostics.into_iter().enumerate() {
+    let obj = cx.empty_object();
+    let code = match d.code {
+      Some(c) => cx.string(c).upcast::<JsValue>(),
+      None => cx.null().upcast(),
+    };
+    obj.set(cx, "code", code)?;
+    let severity = cx.string(d.level);
+    obj.set(cx, "severity", severity)?;
+    let message = cx.string(d.message);
+    obj.set(cx, "message", message)?;
+    let line = cx.number(d.line as f64);
+    obj.set(cx, "line", line)?;
+    let column = cx.number(d.column as f64);
+    obj.set(cx, "column", column)?;
+    let end_line = cx.number(d.end_line as f64);
+    obj.set(cx, "endLine", end_line)?;
+    let end_column = cx.number(d.end_column as f64);
+    obj.set(cx, "endColumn", end_column)?;
+    arr.set(cx, i as u32, obj)?;
+  }
+  Ok(arr)
+}
+
+/// 从 options 对象中读取诊断相关的配置：lang、warningsAsErrors 和 rules。
+fn read_diagnostic_policy(
+  cx: &mut FunctionContext,
+  options: Option<Handle<JsObject>>,
+) -> NeonResult<DiagnosticPolicy> {
+  let mut diagnostic = DiagnosticPolicy::default();
+  let mut lang = None;
+  if let Some(opts) = options {
+    lang = opts
+      .get_opt::<JsString, _, _>(cx, "lang")?
+      .map(|v| v.value(cx));
+    diagnostic.warnings_as_errors = opts
+      .get_opt::<JsBoolean, _, _>(cx, "warningsAsErrors")?
+      .is_some_and(|v| v.value(cx));
+    // rules 形如 `{ JG1002: 'off', JG2103: 'error' }`
+    if let Some(rules) = opts.get_opt::<JsObject, _, _>(cx, "rules")? {
+      let keys = rules.get_own_property_names(cx)?.to_vec(cx)?;
+      for key in keys {
+        let key = key.downcast_or_throw::<JsString, _>(cx)?.value(cx);
+        let value = rules.get::<JsString, _, _>(cx, key.as_str())?.value(cx);
+        let Some(severity) = Severity::parse(&value) else {
+          return cx.throw_error(format!("invalid severity `{}` of rule `{}`", value, key));
+        };
+        diagnostic.rules.insert(key, severity);
+      }
+    }
+  }
+  diagnostic.lang = Lang::resolve(lang.as_deref());
+  Ok(diagnostic)
+}
+
+/// 读取 options 对象中的字符串数组，比如 `hocWrappers: ['withTheme', 'React.memo']`。
+fn read_string_array(
+  cx: &mut FunctionContext,
+  options: Option<Handle<JsObject>>,
+  key: &str,
+) -> NeonResult<Vec<String>> {
+  let Some(arr) = options
+    .map(|opts| opts.get_opt::<JsArray, _, _>(cx, key))
+    .transpose()?
+    .flatten()
+  else {
+    return Ok(vec![]);
+  };
+  arr
+    .to_vec(cx)?
+    .into_iter()
+    .map(|v| Ok(v.downcast_or_throw::<JsString, _>(cx)?.value(cx)))
+    .collect()
 }
 
 fn transform(mut cx: FunctionContext) -> JsResult<JsObject> {
@@ -198,24 +487,130 @@ fn transform(mut cx: FunctionContext) -> JsResult<JsObject> {
   let sourcemap_enabled = cx.argument::<JsBoolean>(3)?.value(&mut cx);
   let intl_type = cx.argument::<JsNumber>(4)?.value(&mut cx) as u8;
   // let hmr_enabled = cx.argument::<JsBoolean>(3)?.value(&mut cx);
-  let (code, parsed_components, map) = inner_transform(
+  // 第 6 个参数是可选的 options 对象，比如 `{ lang: 'en' }`
+  let options = match cx.argument_opt(5) {
+    Some(v) => v.downcast::<JsObject, _>(&mut cx).ok(),
+    None => None,
+  };
+  let diagnostic = read_diagnostic_policy(&mut cx, options)?;
+  let fail_on_error = match options {
+    Some(opts) => opts
+      .get_opt::<JsBoolean, _, _>(&mut cx, "failOnError")?
+      .is_some_and(|v| v.value(&mut cx)),
+    None => false,
+  };
+  let hoc_wrappers = read_string_array(&mut cx, options, "hocWrappers")?;
+  // mode 是模板的编译目标：`'dom'`（默认）、`'ssr'` 或 `'hydrate'`
+  let mode = match options {
+    Some(opts) => opts
+      .get_opt::<JsString, _, _>(&mut cx, "mode")?
+      .map(|v| v.value(&mut cx)),
+    None => None,
+  };
+  let render_mode = match mode {
+    Some(mode) => {
+      let Some(render_mode) = RenderMode::parse(&mode) else {
+        return cx.throw_error(format!("invalid mode `{}`", mode));
+      };
+      render_mode
+    }
+    None => RenderMode::Dom,
+  };
+  let dev = match options {
+    Some(opts) => opts
+      .get_opt::<JsBoolean, _, _>(&mut cx, "dev")?
+      .is_some_and(|v| v.value(&mut cx)),
+    None => false,
+  };
+  let output = inner_transform(
     file_name,
     code_type,
     origin_code,
-    sourcemap_enabled,
-    if intl_type == 0 {
-      IntlType::Disabled
-    } else {
-      IntlType::Enabled(intl_type > 1)
+    TransformOptions {
+      sourcemap_enabled,
+      intl_type: if intl_type == 0 {
+        IntlType::Disabled
+      } else {
+        IntlType::Enabled(intl_type > 1)
+      },
+      diagnostic,
+      hoc_wrappers,
+      render_mode,
+      dev,
     },
   );
+  if fail_on_error {
+    let errors: Vec<_> = output
+      .diagnostics
+      .iter()
+      .filter(|d| d.level == "error")
+      .map(|d| {
+        format!(
+          "{}:{}: [{}] {}",
+          d.line,
+          d.column,
+          d.code.as_deref().unwrap_or("-"),
+          d.message
+        )
+      })
+      .collect();
+    if !errors.is_empty() {
+      return cx.throw_error(errors.join("\n"));
+    }
+  }
   let obj = cx.empty_object();
-  let obj_code = cx.string(code);
-  let obj_map = cx.string(map.unwrap_or("".into()));
-  let parsed_components = cx.string(parsed_components);
+  let obj_code = cx.string(output.code);
+  let obj_map = cx.string(output.map.unwrap_or("".into()));
+  let parsed_components = cx.string(output.parsed_components);
+  let diagnostics = diagnostics_to_js(&mut cx, output.diagnostics)?;
   obj.set(&mut cx, "code", obj_code)?;
   obj.set(&mut cx, "map", obj_map)?;
   obj.set(&mut cx, "parsedComponents", parsed_components)?;
+  obj.set(&mut cx, "diagnostics", diagnostics)?;
+  Ok(obj)
+}
+
+fn analyze(mut cx: FunctionContext) -> JsResult<JsObject> {
+  let file_name = cx.argument::<JsString>(0)?.value(&mut cx);
+  let origin_code = cx.argument::<JsString>(1)?.value(&mut cx);
+  // 第 3 个参数是可选的 options 对象，比如 `{ lang: 'en', intl: true }`
+  let options = match cx.argument_opt(2) {
+    Some(v) => v.downcast::<JsObject, _>(&mut cx).ok(),
+    None => None,
+  };
+  let diagnostic = read_diagnostic_policy(&mut cx, options)?;
+  let intl_enabled = match options {
+    Some(opts) => opts
+      .get_opt::<JsBoolean, _, _>(&mut cx, "intl")?
+      .is_some_and(|v| v.value(&mut cx)),
+    None => false,
+  };
+  let hoc_wrappers = read_string_array(&mut cx, options, "hocWrappers")?;
+  // 和 vite 插件保持一致，只有 tsx 文件才需要检查模板。
+  let code_type = if file_name.ends_with(".tsx") { 2 } else { 1 };
+  let output = inner_analyze(
+    file_name,
+    code_type,
+    origin_code,
+    AnalyzeOptions {
+      intl_type: if intl_enabled {
+        IntlType::Enabled(false)
+      } else {
+        IntlType::Disabled
+      },
+      diagnostic,
+      hoc_wrappers,
+    },
+  );
+  let obj = cx.empty_object();
+  let parsed_components = cx.empty_array();
+  for (i, name) in output.parsed_components.into_iter().enumerate() {
+    let name = cx.string(name);
+    parsed_components.set(&mut cx, i as u32, name)?;
+  }
+  let diagnostics = diagnostics_to_js(&mut cx, output.diagnostics)?;
+  obj.set(&mut cx, "parsedComponents", parsed_components)?;
+  obj.set(&mut cx, "diagnostics", diagnostics)?;
   Ok(obj)
 }
 
@@ -223,12 +618,17 @@ fn transform(mut cx: FunctionContext) -> JsResult<JsObject> {
 fn main(mut cx: ModuleContext) -> NeonResult<()> {
   // println!("rust core loaded");
   cx.export_function("transform", transform)?;
+  cx.export_function("analyze", analyze)?;
   Ok(())
 }
 
 #[test]
 fn test_transform() {
-  let (code, parsed_components, _) = inner_transform(
+  let TransformOutput {
+    code,
+    parsed_components,
+    ..
+  } = inner_transform(
     "test.tsx".into(),
     2,
     "const $jg$ = (src: string, content: string) => src.replace('{:?}', content);
@@ -239,8 +639,11 @@ export default {
     `你好，${$jg$(red, `${$jg$(b, `哦哦`)}：${name}`)}`,
 };"
       .into(),
-    true,
-    IntlType::Disabled,
+    TransformOptions {
+      sourcemap_enabled: true,
+      diagnostic: DiagnosticPolicy::new(Lang::Zh),
+      ..Default::default()
+    },
   );
   println!("PARSED COMPONENTS: {}", parsed_components);
   std::fs::write("target/out.ts", &code).unwrap();
@@ -248,3 +651,65 @@ export default {
   // assert_eq!(code, "x");
   // assert!(false)
 }
+
+#[test]
+fn test_hygiene() {
+  let TransformOutput { code, .. } = inner_transform(
+    "test.tsx".into(),
+    2,
+    "const a0 = 10;
+export function A() {
+  const state = vm({ x: 1 });
+  return <div>{state.x + a0}</div>;
+}"
+    .into(),
+    TransformOptions {
+      diagnostic: DiagnosticPolicy::new(Lang::Zh),
+      ..Default::default()
+    },
+  );
+  // 生成的 ExprWatcher 参数不能遮蔽用户定义的 a0
+  assert!(code.contains("(a01)=>a01 + a0"));
+}
+
+#[test]
+fn test_analyze() {
+  let AnalyzeOutput {
+    parsed_components,
+    diagnostics,
+  } = inner_analyze(
+    "test.tsx".into(),
+    2,
+    "export function A() {
+  return <div ref={a} ref={b}></div>;
+}
+// jinge-custom-element x-b
+export function B(props) { return <p>{props.list.map((x) => <i>{x}</i>)}</p>; }
+const top = <div />;"
+      .into(),
+    AnalyzeOptions {
+      diagnostic: DiagnosticPolicy::new(Lang::En),
+      ..Default::default()
+    },
+  );
+  assert_eq!(parsed_components, ["A", "B"]);
+  // 只检查时组件的模板不生成代码，但未被编译的 jsx 仍然会报告
+  let codes: Vec<_> = diagnostics
+    .iter()
+    .filter_map(|d| d.code.as_deref())
+    .collect();
+  assert_eq!(codes, ["JG2104", "JG2011"]);
+
+  // ts 文件不按 tsx 语法解析，也不检查模板
+  let AnalyzeOutput {
+    parsed_components,
+    diagnostics,
+  } = inner_analyze(
+    "test.ts".into(),
+    1,
+    "export const n = <number>JSON.parse('1');".into(),
+    AnalyzeOptions::default(),
+  );
+  assert!(parsed_components.is_empty());
+  assert!(diagnostics.is_empty());
+}
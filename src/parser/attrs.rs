@@ -1,33 +1,517 @@
 use std::rc::Rc;
 
-use crate::common::emit_error;
+use crate::ast::*;
+use crate::common::{
+  emit_error, emit_error_with_args, global_ident, DiagCode, JINGE_EMPTY_STR,
+  UNITLESS_CSS_PROPERTIES,
+};
 use crate::parser::TemplateParser;
 use hashbrown::HashSet;
 use swc_core::ecma::ast::*;
-use swc_core::{atoms::Atom, common::Spanned};
+use swc_core::{
+  atoms::Atom,
+  common::{Spanned, DUMMY_SP},
+};
 
 use super::expr::{ExprParseResult, ExprVisitor};
+use super::ir::{Event, Value};
 use super::{
-  JINGE_CHILDREN, JINGE_CLASS, JINGE_CLASSNAME, JINGE_DBLCLICK, JINGE_DOUBLECLICK, JINGE_FOR,
-  JINGE_HTML_FOR, JINGE_KEY, JINGE_REF,
+  JINGE_BIND, JINGE_CHILDREN, JINGE_CLASS, JINGE_CLASSNAME, JINGE_DBLCLICK, JINGE_DOUBLECLICK,
+  JINGE_EVT_IDENT, JINGE_FOR, JINGE_HTML_FOR, JINGE_KEY, JINGE_OPTION_IDENT, JINGE_REF,
+  JINGE_STYLE,
 };
 
-pub struct AttrEvt {
-  pub event_name: Atom,
-  pub event_handler: Box<Expr>,
-  pub capture: bool,
-}
 pub struct AttrStore {
   /// ref 属性，例如 `<div ref="some"></div>`
   pub ref_prop: Option<Box<Expr>>,
   /// 事件属性，例如 `<div onClick={(evt) => {}}></div>`
-  pub evt_props: Vec<AttrEvt>,
-  /// 不需要 watch 监听的表达式属性，例如 `<div a={45 + "hello"} b={_someVar.o} c="hello" d={true} disabled ></div>`
-  pub const_props: Vec<(IdentName, Box<Expr>)>,
-  pub watch_props: Vec<(IdentName, ExprParseResult)>,
+  pub evt_props: Vec<Event>,
+  /// 常量属性和需要 watch 监听的表达式属性，例如 `<div a={45 + "hello"} b={this.o} c="hello" disabled ></div>`
+  pub props: Vec<(IdentName, Value)>,
   /// ... 解构写法透传的属性，例如 `<A {...state} />` 本质上就是把 state 作为 A 组件的 props 参数直接传递。
-  /// 这种写法的情况下，不能再有其它 const 或 watch 属性，并且只能出现一次。
+  /// 这种写法的情况下，不能再有其它属性，并且只能出现一次。
   pub spread_prop: Option<Ident>,
+  /// html 元素的 style 对象属性，例如 `<div style={{ color: this.c, width: 10 }}></div>`，参看 [super::ir::Element::style]
+  pub style: Vec<(Atom, Value)>,
+  /// html 元素按条件切换的 class，例如 `class={["btn", { active: this.on }]}` 和 `class:active={this.on}`，参看 [super::ir::Element::class_toggles]
+  pub class_toggles: Vec<(Atom, Value)>,
+  /// 多选 select 的 `bind:value`，参看 [super::ir::Element::select_values]
+  pub select_values: Option<ExprParseResult>,
+}
+
+/// 解析组件属性（以及 Slot 参数等）的值。函数表达式中对函数自身参数的引用不需要监听。
+pub fn parse_value(expr: &Expr) -> Value {
+  let r = match expr {
+    Expr::Lit(_) => return Value::Const(Box::new(expr.clone())),
+    Expr::Fn(_) | Expr::Arrow(_) => {
+      let mut set: HashSet<Atom> = HashSet::new();
+      match expr {
+        Expr::Fn(e) => e.function.params.iter().for_each(|p| {
+          if let Pat::Ident(id) = &p.pat {
+            set.insert(id.sym.clone());
+          }
+        }),
+        Expr::Arrow(e) => e.params.iter().for_each(|p| {
+          if let Pat::Ident(id) = p {
+            set.insert(id.sym.clone());
+          }
+        }),
+        _ => (),
+      }
+      ExprVisitor::new_with_exclude_roots(if set.is_empty() {
+        None
+      } else {
+        Some(Rc::new(set))
+      })
+      .parse(expr)
+    }
+    _ => ExprVisitor::new().parse(expr),
+  };
+  match r {
+    ExprParseResult::None => Value::Const(Box::new(expr.clone())),
+    _ => Value::Bind {
+      expr: Box::new(expr.clone()),
+      watch: r,
+    },
+  }
+}
+
+/// 解析 style 对象的每个属性。只支持 `color: ...`、`"font-size": ...` 和简写的 `color` 形式的属性，不支持 `...` 和计算属性名。
+/// 数值常量的处理和 react 一致：不需要单位的属性（例如 `opacity`、`zIndex`）保持原样，其它属性加上 px，`{ width: 10 }` 即 `width: 10px`。
+/// 需要监听的表达式在运行时原样设置，数值不会自动加上单位。
+fn parse_style_object(obj: &ObjectLit, style: &mut Vec<(Atom, Value)>) {
+  obj.props.iter().for_each(|prop| {
+    let PropOrSpread::Prop(prop) = prop else {
+      emit_error(prop.span(), DiagCode::UnsupportedStyleProp);
+      return;
+    };
+    let (name, value) = match prop.as_ref() {
+      Prop::KeyValue(kv) => match &kv.key {
+        PropName::Ident(id) => (css_property_name(&id.sym), kv.value.as_ref()),
+        PropName::Str(s) => (css_property_name(&s.value), kv.value.as_ref()),
+        _ => {
+          emit_error(kv.key.span(), DiagCode::UnsupportedStyleProp);
+          return;
+        }
+      },
+      Prop::Shorthand(id) => {
+        style.push((
+          css_property_name(&id.sym),
+          parse_value(&Expr::Ident(id.clone())),
+        ));
+        return;
+      }
+      _ => {
+        emit_error(prop.span(), DiagCode::UnsupportedStyleProp);
+        return;
+      }
+    };
+    let value = match css_number_value(&name, value) {
+      Some(v) => Value::Const(ast_create_expr_lit_str(v)),
+      None => parse_value(value),
+    };
+    style.push((name, value));
+  });
+}
+
+/// 需要单位的 css 属性的非 0 数值常量转换为 px 值，例如 `10` 转换为 `"10px"`，`-1.5` 转换为 `"-1.5px"`。其它情况返回 None。
+fn css_number_value(name: &str, value: &Expr) -> Option<Atom> {
+  let n = match value {
+    Expr::Lit(Lit::Num(n)) => n.value,
+    Expr::Unary(UnaryExpr {
+      op: UnaryOp::Minus,
+      arg,
+      ..
+    }) => match arg.as_ref() {
+      Expr::Lit(Lit::Num(n)) => -n.value,
+      _ => return None,
+    },
+    _ => return None,
+  };
+  if n == 0.0 || name.starts_with("--") {
+    return None;
+  }
+  // 带浏览器前缀的属性，例如 `-webkit-flex`，按去掉前缀之后的属性名判断。
+  let unprefixed = ["-webkit-", "-moz-", "-ms-", "-o-"]
+    .iter()
+    .find_map(|p| name.strip_prefix(p))
+    .unwrap_or(name);
+  if UNITLESS_CSS_PROPERTIES
+    .binary_search(&Atom::from(unprefixed))
+    .is_ok()
+  {
+    return None;
+  }
+  Some(format!("{}px", n).into())
+}
+
+/// 解析 clsx 风格的 class 值：字符串是固定的 class，放入 static_class；`{ active: this.on }` 对象的每个属性是按条件切换的 class，
+/// 放入 toggles。数组可以嵌套，`null`、`undefined` 和 `false` 会被忽略。
+/// 数组中 `props.kind` 这样编译时无法确定的项放入 dynamic，参看 [class_string_expr]。
+fn parse_class_value(
+  expr: &Expr,
+  static_class: &mut Vec<Atom>,
+  toggles: &mut Vec<(Atom, Value)>,
+  dynamic: &mut Vec<Expr>,
+) {
+  match expr {
+    Expr::Lit(Lit::Str(s)) => static_class.extend(s.value.split_whitespace().map(Atom::from)),
+    Expr::Lit(Lit::Null(_)) | Expr::Lit(Lit::Bool(Bool { value: false, .. })) => (),
+    Expr::Ident(id) if id.sym == "undefined" => (),
+    Expr::Paren(e) => parse_class_value(&e.expr, static_class, toggles, dynamic),
+    Expr::Array(arr) => arr.elems.iter().for_each(|e| match e {
+      Some(ExprOrSpread { spread: None, expr }) => {
+        parse_class_value(expr, static_class, toggles, dynamic)
+      }
+      Some(e) => emit_error(e.span(), DiagCode::UnsupportedAttrValue),
+      None => (),
+    }),
+    Expr::Object(obj) => obj.props.iter().for_each(|prop| {
+      let PropOrSpread::Prop(prop) = prop else {
+        emit_error(prop.span(), DiagCode::UnsupportedAttrValue);
+        return;
+      };
+      let Prop::KeyValue(kv) = prop.as_ref() else {
+        emit_error(prop.span(), DiagCode::UnsupportedAttrValue);
+        return;
+      };
+      let names = match &kv.key {
+        PropName::Ident(id) => id.sym.as_str(),
+        PropName::Str(s) => s.value.as_str(),
+        _ => {
+          emit_error(kv.key.span(), DiagCode::UnsupportedAttrValue);
+          return;
+        }
+      };
+      names
+        .split_whitespace()
+        .for_each(|name| push_class_toggle(name.into(), &kv.value, static_class, toggles));
+    }),
+    _ => dynamic.push(expr.clone()),
+  }
+}
+
+/// class 数组中有编译时无法确定的项时，整个 class 值拼接为字符串表达式，作为需要监听的 class 属性：
+/// `["btn", props.kind, { active: this.on }]` 转换为 `"btn" + (this.on ? " active" : "") + (" " + (props.kind || ""))`。
+/// 这些项在运行时直接拼接为字符串，值是数组或对象时不会再按 clsx 的规则展开。
+fn class_string_expr(
+  static_class: Vec<Atom>,
+  toggles: Vec<(Atom, Value)>,
+  dynamic: Vec<Expr>,
+) -> Box<Expr> {
+  let bin = |op, left, right| {
+    Box::new(Expr::Bin(BinExpr {
+      span: DUMMY_SP,
+      op,
+      left,
+      right,
+    }))
+  };
+  let empty = || ast_create_expr_lit_str(JINGE_EMPTY_STR.clone());
+  let mut parts: Vec<Box<Expr>> = vec![];
+  if !static_class.is_empty() {
+    let names: Vec<_> = static_class.iter().map(|c| c.as_str()).collect();
+    parts.push(ast_create_expr_lit_str(names.join(" ").into()));
+  }
+  // 除了第一项，每一项都以空格开头。
+  toggles.into_iter().for_each(|(name, cond)| {
+    let name = if parts.is_empty() {
+      name.to_string()
+    } else {
+      format!(" {}", name)
+    };
+    parts.push(Box::new(Expr::Cond(CondExpr {
+      span: DUMMY_SP,
+      test: cond.into_expr(),
+      cons: ast_create_expr_lit_str(name.into()),
+      alt: empty(),
+    })));
+  });
+  dynamic.into_iter().for_each(|e| {
+    let e = Box::new(Expr::Paren(ParenExpr {
+      span: DUMMY_SP,
+      expr: bin(BinaryOp::LogicalOr, Box::new(e), empty()),
+    }));
+    parts.push(if parts.is_empty() {
+      e
+    } else {
+      bin(BinaryOp::Add, ast_create_expr_lit_str(" ".into()), e)
+    });
+  });
+  let mut parts = parts.into_iter();
+  let first = parts.next().unwrap();
+  parts.fold(first, |left, right| bin(BinaryOp::Add, left, right))
+}
+
+/// 固定的 class 和按条件切换的 class 与 class 属性合并：
+/// - `class="a"` 字符串属性直接拼接固定的 class；
+/// - 不需要监听的非字符串 class 无法在编译时拼接，固定的 class 转换为条件恒为 true 的切换；
+/// - 需要监听的 class 属性每次更新都会用 setAttribute 覆盖切换的 class，不支持一起使用。
+fn merge_class(attrs: &mut AttrStore, static_class: Vec<Atom>) {
+  let class = attrs
+    .props
+    .iter_mut()
+    .find(|(name, _)| JINGE_CLASS.eq(&name.sym))
+    .map(|(_, value)| value);
+  let join_class = |prefix: &str| {
+    std::iter::once(prefix)
+      .chain(static_class.iter().map(|c| c.as_str()))
+      .filter(|c| !c.is_empty())
+      .collect::<Vec<_>>()
+      .join(" ")
+  };
+  match class {
+    Some(Value::Bind { expr, .. }) => {
+      emit_error(expr.span(), DiagCode::ClassBindWithToggles);
+      attrs.class_toggles.clear();
+    }
+    Some(Value::Const(v)) => {
+      if let Expr::Lit(Lit::Str(s)) = v.as_mut() {
+        *s = Str::from(join_class(&s.value));
+      } else {
+        let toggles = static_class.iter().cloned().map(|c| {
+          (
+            c,
+            Value::Const(Box::new(Expr::Lit(Lit::Bool(Bool::from(true))))),
+          )
+        });
+        attrs.class_toggles.splice(0..0, toggles);
+      }
+    }
+    None if !static_class.is_empty() => attrs.props.push((
+      IdentName::from(JINGE_CLASS.clone()),
+      Value::Const(ast_create_expr_lit_str(join_class("").into())),
+    )),
+    None => (),
+  }
+}
+
+/// 条件是 bool 字面量时直接确定，`true` 转换为固定的 class，`false` 忽略。
+fn push_class_toggle(
+  name: Atom,
+  cond: &Expr,
+  static_class: &mut Vec<Atom>,
+  toggles: &mut Vec<(Atom, Value)>,
+) {
+  match cond {
+    Expr::Lit(Lit::Bool(b)) if b.value => static_class.push(name),
+    Expr::Lit(Lit::Bool(_)) => (),
+    _ => toggles.push((name, parse_value(cond))),
+  }
+}
+
+/// 驼峰写法的 style 属性转换为 css 属性名：`fontSize` 转换为 `font-size`，`WebkitTransform` 转换为 `-webkit-transform`，
+/// `msTransform` 转换为 `-ms-transform`。已经是 css 属性名的（包括 `--main-color` 这样的自定义属性）保持不变。
+fn css_property_name(name: &str) -> Atom {
+  if name.starts_with("--") {
+    return name.into();
+  }
+  let mut out = String::with_capacity(name.len() + 4);
+  if name.starts_with("ms") && name[2..].starts_with(|c: char| c.is_ascii_uppercase()) {
+    out.push('-');
+  }
+  name.chars().for_each(|c| {
+    if c.is_ascii_uppercase() {
+      out.push('-');
+      out.push(c.to_ascii_lowercase());
+    } else {
+      out.push(c);
+    }
+  });
+  out.into()
+}
+
+/// 双向绑定从事件的 target 元素读取新值的方式。
+enum BindingRead {
+  /// `e.target.value`、`e.target.checked` 或者 `e.target.valueAsNumber`
+  Prop(&'static str),
+  /// 单选框：选中时把 input 的 value 赋给绑定的值，同一组的单选框绑定同一个值，参看 [radio_checked_expr]
+  RadioValue,
+  /// 多选 select：`Array.from(e.target.selectedOptions, (o) => o.value)`
+  SelectedOptions,
+}
+
+/// 查找元素上的普通属性，返回属性的值（没有值的属性为 None）。
+fn find_attr<'a>(attrs: &'a [JSXAttrOrSpread], name: &str) -> Option<&'a Option<JSXAttrValue>> {
+  attrs.iter().find_map(|attr| match attr {
+    JSXAttrOrSpread::JSXAttr(JSXAttr {
+      name: JSXAttrName::Ident(an),
+      value,
+      ..
+    }) if an.sym == name => Some(value),
+    _ => None,
+  })
+}
+
+/// 根据元素和绑定的属性确定监听的事件以及读取新值的方式，不支持的组合返回 None。
+/// input 的 type 不是字符串常量时按文本输入框处理。
+fn binding_kind(
+  tag: &str,
+  attrs: &[JSXAttrOrSpread],
+  prop: &str,
+) -> Option<(&'static str, BindingRead)> {
+  let static_attr = |name: &str| find_attr(attrs, name);
+  let input_type = match static_attr("type") {
+    Some(Some(JSXAttrValue::Lit(Lit::Str(s)))) => s.value.as_str(),
+    _ => "text",
+  };
+  let multiple = match static_attr("multiple") {
+    Some(None) => true,
+    Some(Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+      expr: JSXExpr::Expr(e),
+      ..
+    }))) => matches!(e.as_ref(), Expr::Lit(Lit::Bool(b)) if b.value),
+    _ => false,
+  };
+  match (tag, prop) {
+    ("input", "value") => match input_type {
+      "checkbox" | "radio" => None,
+      "number" | "range" => Some(("input", BindingRead::Prop("valueAsNumber"))),
+      _ => Some(("input", BindingRead::Prop("value"))),
+    },
+    ("input", "checked") if input_type == "checkbox" => {
+      Some(("change", BindingRead::Prop("checked")))
+    }
+    ("input", "checked") if input_type == "radio" => Some(("change", BindingRead::RadioValue)),
+    ("textarea", "value") => Some(("input", BindingRead::Prop("value"))),
+    ("select", "value") if multiple => Some(("change", BindingRead::SelectedOptions)),
+    ("select", "value") => Some(("change", BindingRead::Prop("value"))),
+    _ => None,
+  }
+}
+
+/// 单选框 `bind:checked={state.pick}` 的选中状态：`state.pick === value`。
+/// value 是元素上的 value 属性，没有 value 属性时和浏览器一致，使用默认值 `"on"`。
+fn radio_checked_expr(all_attrs: &[JSXAttrOrSpread], target: &Expr) -> Box<Expr> {
+  let value = match find_attr(all_attrs, "value") {
+    Some(Some(JSXAttrValue::Lit(lit))) => Box::new(Expr::Lit(lit.clone())),
+    Some(Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+      expr: JSXExpr::Expr(e),
+      ..
+    }))) => e.clone(),
+    _ => ast_create_expr_lit_str("on".into()),
+  };
+  Box::new(Expr::Bin(BinExpr {
+    span: DUMMY_SP,
+    op: BinaryOp::EqEqEq,
+    left: Box::new(target.clone()),
+    right: value,
+  }))
+}
+
+/// 解析 `bind:value={state.name}` 双向绑定，转换为监听 `state.name` 设置元素属性，以及在 input/change 事件中把新值赋给 `state.name`，
+/// 等价于 `value={state.name} onInput={(e) => state.name = e.target.value}`。
+fn parse_binding(
+  tag: &str,
+  all_attrs: &[JSXAttrOrSpread],
+  nn: &JSXNamespacedName,
+  value: &Option<JSXAttrValue>,
+  attrs: &mut AttrStore,
+) {
+  let prop = &nn.name.sym;
+  let Some((event_name, read)) = binding_kind(tag, all_attrs, prop) else {
+    emit_error_with_args(nn.span, DiagCode::InvalidBinding, &[prop]);
+    return;
+  };
+  let Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+    expr: JSXExpr::Expr(target),
+    ..
+  })) = value
+  else {
+    emit_error(nn.span, DiagCode::BindingTarget);
+    return;
+  };
+  // 只有能够监听的成员表达式才可以同时作为赋值的目标，例如 `state.name`、`this.form.age`。
+  let Expr::Member(member) = target.as_ref() else {
+    emit_error(target.span(), DiagCode::BindingTarget);
+    return;
+  };
+  let watch = ExprVisitor::new().parse(target);
+  if matches!(watch, ExprParseResult::None) {
+    emit_error(target.span(), DiagCode::BindingTarget);
+    return;
+  }
+
+  let evt_target = || {
+    ast_create_expr_member(
+      ast_create_expr_ident(JINGE_EVT_IDENT.ident()),
+      MemberProp::Ident(IdentName::from("target")),
+    )
+  };
+  let new_value = match read {
+    BindingRead::Prop(p) => ast_create_expr_member(evt_target(), MemberProp::Ident(p.into())),
+    // 单选框只在被选中时触发 change 事件，此时直接赋值为它的 value。
+    BindingRead::RadioValue => {
+      ast_create_expr_member(evt_target(), MemberProp::Ident(IdentName::from("value")))
+    }
+    BindingRead::SelectedOptions => ast_create_expr_call(
+      ast_create_expr_member(
+        ast_create_expr_ident(global_ident("Array".into())),
+        MemberProp::Ident(IdentName::from("from")),
+      ),
+      vec![
+        ast_create_arg_expr(ast_create_expr_member(
+          evt_target(),
+          MemberProp::Ident(IdentName::from("selectedOptions")),
+        )),
+        ast_create_arg_expr(ast_create_expr_arrow_fn(
+          vec![Pat::Ident(BindingIdent::from(JINGE_OPTION_IDENT.ident()))],
+          Box::new(BlockStmtOrExpr::Expr(ast_create_expr_member(
+            ast_create_expr_ident(JINGE_OPTION_IDENT.ident()),
+            MemberProp::Ident(IdentName::from("value")),
+          ))),
+        )),
+      ],
+    ),
+  };
+  let handler = ast_create_expr_arrow_fn(
+    vec![Pat::Ident(BindingIdent::from(JINGE_EVT_IDENT.ident()))],
+    Box::new(BlockStmtOrExpr::Expr(Box::new(Expr::Assign(AssignExpr {
+      span: DUMMY_SP,
+      op: AssignOp::Assign,
+      left: AssignTarget::Simple(SimpleAssignTarget::Member(member.clone())),
+      right: new_value,
+    })))),
+  );
+  attrs.evt_props.push(Event {
+    event_name: event_name.into(),
+    event_handler: handler,
+    capture: false,
+  });
+  if matches!(read, BindingRead::SelectedOptions) {
+    attrs.select_values = Some(watch);
+  } else if matches!(read, BindingRead::RadioValue) {
+    let checked = radio_checked_expr(all_attrs, target);
+    attrs
+      .props
+      .push((IdentName::from(prop.clone()), parse_value(&checked)));
+  } else {
+    attrs.props.push((
+      IdentName::from(prop.clone()),
+      Value::Bind {
+        expr: target.clone(),
+        watch,
+      },
+    ));
+  }
+}
+
+/// react 风格的 `xlinkHref`、`xmlLang`、`xmlnsXlink` 等属性名转换为 `xlink:href`、`xml:lang`、`xmlns:xlink`。
+fn namespaced_attr_name(name: &str) -> Option<Atom> {
+  ["xmlns", "xlink", "xml"].iter().find_map(|prefix| {
+    let rest = name.strip_prefix(prefix)?;
+    let mut chars = rest.chars();
+    let first = chars.next().filter(|c| c.is_ascii_uppercase())?;
+    Some(
+      format!(
+        "{}:{}{}",
+        prefix,
+        first.to_ascii_lowercase(),
+        chars.as_str()
+      )
+      .into(),
+    )
+  })
 }
 
 impl TemplateParser {
@@ -35,44 +519,84 @@ impl TemplateParser {
     let mut attrs = AttrStore {
       ref_prop: None,
       evt_props: vec![],
-      const_props: vec![],
-      watch_props: vec![],
+      props: vec![],
       spread_prop: None,
+      style: vec![],
+      class_toggles: vec![],
+      select_values: None,
+    };
+    let tag = match &n.opening.name {
+      JSXElementName::Ident(id) => id.sym.as_str(),
+      _ => "",
     };
+    let mut static_class: Vec<Atom> = vec![];
 
     n.opening.attrs.iter().for_each(|attr| match attr {
       JSXAttrOrSpread::SpreadElement(s) => {
         let Expr::Ident(id) = s.expr.as_ref() else {
-          emit_error(s.span(), "解构写法...后必须是 Ident");
+          emit_error(s.span(), DiagCode::SpreadNotIdent);
           return;
         };
         if attrs.spread_prop.is_some() {
-          emit_error(s.span(), "解构写法透传属性只能出现一次");
+          emit_error(s.span(), DiagCode::SpreadOnlyOnce);
         } else {
           attrs.spread_prop.replace(id.clone());
         }
       }
       JSXAttrOrSpread::JSXAttr(attr) => {
-        let JSXAttrName::Ident(an) = &attr.name else {
-          return;
+        let an = match &attr.name {
+          JSXAttrName::Ident(an) => an.clone(),
+          // `class:active={this.on}` 按条件切换单个 class，不带值时等价于 `class:active={true}`。
+          JSXAttrName::JSXNamespacedName(nn) if !is_component && JINGE_CLASS.eq(&nn.ns.sym) => {
+            match &attr.value {
+              None => static_class.push(nn.name.sym.clone()),
+              Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                expr: JSXExpr::Expr(cond),
+                ..
+              })) => push_class_toggle(
+                nn.name.sym.clone(),
+                cond,
+                &mut static_class,
+                &mut attrs.class_toggles,
+              ),
+              Some(val) => emit_error(val.span(), DiagCode::UnsupportedAttrValue),
+            }
+            return;
+          }
+          JSXAttrName::JSXNamespacedName(nn) if !is_component && JINGE_BIND.eq(&nn.ns.sym) => {
+            parse_binding(tag, &n.opening.attrs, nn, &attr.value, &mut attrs);
+            return;
+          }
+          // `xlink:href` 这样带命名空间前缀的属性只能用于 html/svg 元素，组件的 props 不支持。
+          JSXAttrName::JSXNamespacedName(nn) if !is_component => {
+            IdentName::new(format!("{}:{}", nn.ns.sym, nn.name.sym).into(), nn.span)
+          }
+          JSXAttrName::JSXNamespacedName(nn) => {
+            emit_error_with_args(
+              nn.span,
+              DiagCode::ComponentNamespacedAttr,
+              &[&format!("{}:{}", nn.ns.sym, nn.name.sym)],
+            );
+            return;
+          }
         };
         if JINGE_CHILDREN.eq(&an.sym) {
-          emit_error(an.span(), "警告：不能使用 children 属性名，如果是定义  Slot，请使用 jsx 子元素的方式定义！");
+          emit_error(an.span(), DiagCode::ChildrenAttr);
         } else if JINGE_KEY.eq(&an.sym) {
           // 当前版本 key 属性暂时仅用于在语法层面兼容 react/vue，实际没有作用，直接忽略。
           // 列表循环使用的 <For> 组件，等价的属性为 `keyFn` 属性。
           // 此外，map 函数会被编译器自动转换为 <For> 组件，转换时会找到元素上的 key 属性也同时转换为 keyFn 属性。参看 ./map.rs 和 ./map_key.rs
         } else if JINGE_REF.eq(&an.sym) {
           if attrs.ref_prop.is_some() {
-            emit_error(attr.span(), "不能重复指定 ref");
+            emit_error(attr.span(), DiagCode::DuplicateRef);
             return;
           }
           let Some(JSXAttrValue::JSXExprContainer(val)) = &attr.value else {
-            emit_error(attr.value.span(), "ref 属性值不合法");
+            emit_error(attr.value.span(), DiagCode::InvalidRef);
             return;
           };
           let JSXExpr::Expr(val) = &val.expr else {
-            emit_error(val.expr.span(), "ref 属性值不合法");
+            emit_error(val.expr.span(), DiagCode::InvalidRef);
             return;
           };
           attrs.ref_prop.replace(val.clone());
@@ -80,23 +604,25 @@ impl TemplateParser {
           && matches!(an.sym.chars().nth(2), Some(c) if c >= 'A' && c <= 'Z')
         {
           let Some(JSXAttrValue::JSXExprContainer(val)) = &attr.value else {
-            emit_error(attr.span(), "事件属性的属性值必须是箭头函数");
+            emit_error(attr.span(), DiagCode::EventNotArrow);
             return;
           };
           let JSXExpr::Expr(val) = &val.expr else {
-            emit_error(attr.span(), "事件属性的属性值必须是箭头函数");
+            emit_error(attr.span(), DiagCode::EventNotArrow);
             return;
           };
           if !matches!(val.as_ref(), Expr::Arrow(_)) {
-            emit_error(attr.span(), "事件属性的属性值必须是箭头函数");
+            emit_error(attr.span(), DiagCode::EventNotArrow);
             return;
           };
           if is_component {
-            attrs.const_props.push((IdentName::from(an.sym.clone()), val.clone()));
+            attrs
+              .props
+              .push((IdentName::from(an.sym.clone()), Value::Const(val.clone())));
           } else {
             let mut event_name = &an.sym[2..];
             let mut capture = false;
-            if  event_name.ends_with("Capture") {
+            if event_name.ends_with("Capture") {
               event_name = &event_name[..event_name.len() - 7];
               capture = true;
             }
@@ -104,7 +630,7 @@ impl TemplateParser {
             if JINGE_DOUBLECLICK.eq(&event_name) {
               event_name = JINGE_DBLCLICK.clone();
             }
-            attrs.evt_props.push(AttrEvt {
+            attrs.evt_props.push(Event {
               event_name,
               event_handler: val.clone(),
               capture,
@@ -116,6 +642,8 @@ impl TemplateParser {
               IdentName::from(JINGE_CLASS.clone())
             } else if JINGE_HTML_FOR.eq(&an.sym) {
               IdentName::from(JINGE_FOR.clone())
+            } else if let Some(name) = namespaced_attr_name(&an.sym) {
+              IdentName::from(name)
             } else {
               an.clone()
             }
@@ -126,8 +654,8 @@ impl TemplateParser {
             match val {
               JSXAttrValue::Lit(val) => {
                 attrs
-                  .const_props
-                  .push((attr_name, Box::new(Expr::Lit(val.clone()))));
+                  .props
+                  .push((attr_name, Value::Const(Box::new(Expr::Lit(val.clone())))));
               }
               JSXAttrValue::JSXExprContainer(val) => match &val.expr {
                 JSXExpr::JSXEmptyExpr(_) => (),
@@ -137,85 +665,260 @@ impl TemplateParser {
                   | Expr::JSXFragment(_)
                   | Expr::JSXMember(_)
                   | Expr::JSXNamespacedName(_) => {
-                    emit_error(val.expr.span(), "不支持 JSX 元素作为属性值");
+                    emit_error(val.expr.span(), DiagCode::JsxAttrValue);
                   }
-                  Expr::Lit(val) => {
-                    attrs
-                      .const_props
-                      .push((attr_name, Box::new(Expr::Lit(val.clone()))));
+                  Expr::Fn(_) | Expr::Arrow(_) if !is_component => {
+                    emit_error(attr.name.span(), DiagCode::FnAttrValue)
                   }
-                  Expr::Fn(_) | Expr::Arrow(_) => {
-                    if !is_component {
-                      emit_error(
-                        attr.name.span(),
-                        "不支持函数作为属性值。如果是想传递事件，请使用 on 打头的属性名，例如 onClick",
-                      )
-                    } else {
-                      let mut set: HashSet<Atom> = HashSet::new();
-                      match expr.as_ref() {
-                        Expr::Fn(e) => {
-                           e.function.params.iter().for_each(|p| {
-                             if let Pat::Ident(id) = &p.pat {
-                               set.insert(id.sym.clone());
-                             }
-                           })
-                        },
-                        Expr::Arrow(e) => {
-                          e.params.iter().for_each(|p| {
-                            if let Pat::Ident(id) = p {
-                              set.insert(id.sym.clone());
-                            }
-                          })
-                        },
-                        _ => ()
-                      }
-                      // println!("{:?}", set);
-                      let r = ExprVisitor::new_with_exclude_roots(if set.is_empty() {
-                        None
-                      } else {
-                        Some(Rc::new(set))
-                      }).parse(expr.as_ref());
-                      match r {
-                        ExprParseResult::None => {
-                          attrs.const_props.push((attr_name, expr.clone()));
-                        }
-                        _ => {
-                          attrs.watch_props.push((attr_name, r))
-                        }
-                      }
-                    }
+                  Expr::Object(obj) if !is_component && JINGE_STYLE.eq(&attr_name.sym) => {
+                    parse_style_object(obj, &mut attrs.style)
                   }
-                  _ => {
-                    let r = ExprVisitor::new().parse(expr.as_ref());
-                    match r {
-                      ExprParseResult::None => {
-                        attrs.const_props.push((attr_name, expr.clone()));
-                      }
-                      _ => {
-                        attrs.watch_props.push((attr_name, r))
-                      }
+                  Expr::Array(_) | Expr::Object(_)
+                    if !is_component && JINGE_CLASS.eq(&attr_name.sym) =>
+                  {
+                    let (mut names, mut toggles, mut dynamic) = (vec![], vec![], vec![]);
+                    parse_class_value(expr, &mut names, &mut toggles, &mut dynamic);
+                    if dynamic.is_empty() {
+                      static_class.append(&mut names);
+                      attrs.class_toggles.append(&mut toggles);
+                    } else {
+                      let value = class_string_expr(names, toggles, dynamic);
+                      attrs.props.push((attr_name, parse_value(&value)));
                     }
                   }
+                  _ => attrs.props.push((attr_name, parse_value(expr))),
                 },
               },
-              _ => emit_error(val.span(), "不支持该类型的属性值。"),
+              _ => emit_error(val.span(), DiagCode::UnsupportedAttrValue),
             }
           } else {
             // bool attribute
-            attrs
-              .const_props
-              .push((attr_name, Box::new(Expr::Lit(Lit::Bool(Bool::from(true))))));
+            attrs.props.push((
+              attr_name,
+              Value::Const(Box::new(Expr::Lit(Lit::Bool(Bool::from(true))))),
+            ));
           }
         }
       }
     });
 
-    if attrs.spread_prop.is_some()
-      && (!attrs.const_props.is_empty() || !attrs.watch_props.is_empty())
-    {
+    if !static_class.is_empty() || !attrs.class_toggles.is_empty() {
+      merge_class(&mut attrs, static_class);
+    }
+
+    if attrs.spread_prop.is_some() && !attrs.props.is_empty() {
       let id = attrs.spread_prop.take();
-      emit_error(id.span(), "解构写法透传属性只能出现一次");
+      emit_error(id.span(), DiagCode::SpreadOnlyOnce);
     }
     attrs
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::common::RenderMode;
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_style_object() {
+    let src = "export function A(props) {
+  return <p style={{ color: props.c, fontSize: '12px', WebkitTransform: 'none' }} onClick={() => {}}>x</p>;
+}";
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform("test.tsx".into(), 2, src.into(), Default::default());
+    assert!(diagnostics.is_empty());
+    assert!(!code.contains("\"style\""));
+    assert!(code.contains(r#".style.setProperty("font-size", '12px');"#));
+    assert!(code.contains(r#".style.setProperty("-webkit-transform", 'none');"#));
+    assert!(code.contains(r#".style.setProperty("color", v)"#));
+
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      src.into(),
+      TransformOptions {
+        render_mode: RenderMode::Ssr,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(r#"ssrAttr$jg$("style", ssrStyle$jg$({"#));
+    assert!(code.contains(r#""font-size": '12px',"#));
+
+    // 数值常量和 react 一致：需要单位的属性加上 px，不需要单位的属性和 0 保持原样
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  return <p style={{ width: 10, marginTop: -1.5, top: 0, zIndex: 2, WebkitFlex: 1, height: props.h }}>x</p>;
+}"
+      .into(),
+      TransformOptions {
+        render_mode: RenderMode::Ssr,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(r#""width": "10px","#));
+    assert!(code.contains(r#""margin-top": "-1.5px","#));
+    assert!(code.contains(r#""top": 0,"#));
+    assert!(code.contains(r#""z-index": 2,"#));
+    assert!(code.contains(r#""-webkit-flex": 1,"#));
+    assert!(code.contains(r#""height": props.h"#));
+
+    // 展开和计算属性名报告专门的错误，简写属性正常解析
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  const color = 'red';
+  return <p style={{ ...props.base, color, [props.k]: 1 }}>x</p>;
+}"
+      .into(),
+      Default::default(),
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .map(|d| (d.code.as_deref().unwrap(), d.column))
+      .collect();
+    assert_eq!(codes, [("JG2115", 22), ("JG2115", 44)]);
+    assert!(code.contains(r#".style.setProperty("color", color);"#));
+  }
+
+  #[test]
+  fn test_class_toggles() {
+    let src = "export function A(props) {
+  return <p className={['btn', { active: props.on, fixed: true, off: false }]} class:sel={props.s}>x</p>;
+}";
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform("test.tsx".into(), 2, src.into(), Default::default());
+    assert!(diagnostics.is_empty());
+    assert!(code.contains(r#""class": "btn fixed""#));
+    assert!(code.contains(r#".classList.toggle("active", !!v)"#));
+    assert!(code.contains(r#".classList.toggle("sel", !!v)"#));
+    assert!(!code.contains("\"off\""));
+
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      src.into(),
+      TransformOptions {
+        render_mode: RenderMode::Ssr,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      r#"'<p class="btn fixed' + (props.on ? " active" : "") + (props.s ? " sel" : "") + '">"#
+    ));
+
+    // 不需要监听的非字符串 class 和固定的 class 合并为一个属性，需要监听的 class 不能和切换的 class 一起使用
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "const CLS = 'card';
+export function A(props) {
+  return <div><p class={CLS} class:x>a</p><i class={props.cls} class:y={props.on}>b</i></div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code.as_deref(), Some("JG2113"));
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 53));
+    assert_eq!(code.matches("\"class\"").count(), 2);
+    assert!(code.contains(r#".classList.toggle("x", !!true)"#));
+    assert!(!code.contains(r#"classList.toggle("y""#));
+
+    // 数组中编译时无法确定的项和其它 class 拼接为需要监听的 class 属性
+    let src = "export function A(props) {
+  return <p class={['btn', props.kind, { active: props.on }]}>x</p>;
+}";
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform("test.tsx".into(), 2, src.into(), Default::default());
+    assert!(diagnostics.is_empty());
+    assert!(code.contains(r#"=>"btn" + (a0 ? " active" : "") + (" " + (a1 || ""))), "#));
+    assert!(code.contains(r#", "class", v), this);"#));
+    assert!(!code.contains("classList"));
+
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      src.into(),
+      TransformOptions {
+        render_mode: RenderMode::Ssr,
+        ..Default::default()
+      },
+    );
+    assert!(code.contains(
+      r#"ssrAttr$jg$("class", "btn" + (props.on ? " active" : "") + (" " + (props.kind || "")))"#
+    ));
+  }
+
+  #[test]
+  fn test_two_way_binding() {
+    let src = "export function A(props) {
+  return <div>
+    <input bind:value={props.form.name} />
+    <input type=\"checkbox\" bind:checked={props.ok} />
+    <select multiple bind:value={props.tags}><option>a</option></select>
+    <textarea bind:checked={props.ok} />
+    <input bind:value={props.a + 1} />
+  </div>;
+}";
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform("test.tsx".into(), 2, src.into(), Default::default());
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .map(|d| d.code.as_deref().unwrap())
+      .collect();
+    assert_eq!(codes, ["JG2111", "JG2112"]);
+    assert!(code.contains(r#", "input", (e)=>props.form.name = e.target.value);"#));
+    assert!(code.contains(r#".value = v"#));
+    assert!(code.contains(r#", "change", (e)=>props.ok = e.target.checked);"#));
+    assert!(
+      code.contains(r#"(e)=>props.tags = Array.from(e.target.selectedOptions, (o)=>o.value)"#)
+    );
+    assert!(code.contains(r#"(v)=>setSelectValues$jg$("#));
+
+    // 单选框绑定同一组的选中值：选中时赋值为 input 的 value，值相等时选中。
+    // 局部变量不会遮蔽全局的 Array。
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  const Array = 1;
+  return <div>
+    <input type=\"radio\" value=\"a\" bind:checked={props.pick} />
+    <input type=\"radio\" value={props.b} bind:checked={props.pick} />
+    <select multiple bind:value={props.tags}><option>a</option></select>
+  </div>;
+}"
+      .into(),
+      Default::default(),
+    );
+    assert!(diagnostics.is_empty());
+    assert_eq!(
+      code
+        .matches(r#", "change", (e)=>props.pick = e.target.value);"#)
+        .count(),
+      2
+    );
+    assert!(code.contains(r#"], (a0)=>a0 === "a"), "#));
+    assert!(code.contains("], (a0, a1)=>a0 === a1), "));
+    assert!(code.contains(".checked = v"));
+    assert!(code.contains("const Array1 = 1;"));
+    assert!(code.contains("props.tags = Array.from("));
+  }
+}
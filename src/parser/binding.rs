@@ -0,0 +1,64 @@
+use swc_core::{atoms::Atom, ecma::ast::*};
+
+use crate::common::JINGE;
+
+/// 模块中从 `jinge` 导入的某个函数或组件（例如 t 函数、Dynamic 组件）的本地绑定。
+/// 支持 `import { t } from 'jinge'`、`import { t as tr } from 'jinge'` 以及 `import * as jg from 'jinge'` 后的 `jg.t()`。
+///
+/// 绑定通过 Ident 的 sym + SyntaxContext 比较（resolver 已经处理过作用域），因此组件内部定义的同名局部变量会正确地遮蔽导入的绑定。
+#[derive(Default)]
+pub struct JingeBinding {
+  name: Atom,
+  locals: Vec<Id>,
+  namespaces: Vec<Id>,
+}
+impl JingeBinding {
+  pub fn collect(module: &Module, name: &Atom) -> Self {
+    let mut binding = Self {
+      name: name.clone(),
+      ..Default::default()
+    };
+    module.body.iter().for_each(|item| {
+      let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item else {
+        return;
+      };
+      if import.type_only || !JINGE.eq(&import.src.value) {
+        return;
+      }
+      import.specifiers.iter().for_each(|spec| match spec {
+        ImportSpecifier::Named(spec) => {
+          if spec.is_type_only {
+            return;
+          }
+          let imported = match &spec.imported {
+            Some(ModuleExportName::Ident(id)) => &id.sym,
+            Some(ModuleExportName::Str(s)) => &s.value,
+            None => &spec.local.sym,
+          };
+          if binding.name.eq(imported) {
+            binding.locals.push(spec.local.to_id());
+          }
+        }
+        ImportSpecifier::Namespace(spec) => binding.namespaces.push(spec.local.to_id()),
+        ImportSpecifier::Default(_) => (),
+      })
+    });
+    binding
+  }
+
+  /// 判定 expr 是否引用了从 jinge 导入的该绑定。
+  pub fn is(&self, expr: &Expr) -> bool {
+    match expr {
+      Expr::Ident(id) => self.locals.iter().any(|l| l.0 == id.sym && l.1 == id.ctxt),
+      Expr::Member(MemberExpr {
+        obj,
+        prop: MemberProp::Ident(prop),
+        ..
+      }) => {
+        self.name.eq(&prop.sym)
+          && matches!(obj.as_ref(), Expr::Ident(ns) if self.namespaces.iter().any(|l| l.0 == ns.sym && l.1 == ns.ctxt))
+      }
+      _ => false,
+    }
+  }
+}
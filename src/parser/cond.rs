@@ -1,26 +1,21 @@
-use swc_common::Spanned;
 use swc_core::{common::DUMMY_SP, ecma::ast::*};
 
 use crate::ast::*;
 
 use super::{
+  attrs::parse_value,
   expr::{ExprParseResult, ExprVisitor},
-  tpl::{tpl_render_const_text, tpl_render_expr_text},
-  TemplateParser, JINGE_EMPTY_STR, JINGE_IMPORT_IF, JINGE_UNDEFINED, JINGE_V_IDENT,
+  ir::{If, Node},
+  TemplateParser, JINGE_EMPTY_STR, JINGE_UNDEFINED, JINGE_V_IDENT,
 };
 
 lazy_static::lazy_static! {
-  static ref EXPECT: IdentName = IdentName::from("expect");
   static ref TRUE: IdentName = IdentName::from("true");
   static ref FALSE: IdentName = IdentName::from("false");
 }
 
-/// 将形如 `test ? cons : alt` 的二元条件表达式，转换为 `If` 组件： `<If expect={test}>{{ true: cons, false: alt }}</If>`
-fn gen_if_component(
-  test: &Box<Expr>,
-  alt: Option<&Box<Expr>>,
-  cons: Option<&Box<Expr>>,
-) -> JSXElement {
+/// 将形如 `test ? cons : alt` 的二元条件表达式的分支，转换为 `If` 组件的 Slot： `{{ true: cons, false: alt }}`
+fn gen_if_slots(alt: Option<&Expr>, cons: Option<&Expr>) -> JSXElementChild {
   let mut slots = Vec::with_capacity(if alt.is_none() || cons.is_none() {
     1
   } else {
@@ -29,47 +24,26 @@ fn gen_if_component(
   if let Some(cons) = cons {
     slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
       key: PropName::Ident(TRUE.clone()),
-      value: cons.clone(),
+      value: Box::new(cons.clone()),
     }))));
   }
   if let Some(alt) = alt {
     slots.push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
       key: PropName::Ident(FALSE.clone()),
-      value: alt.clone(),
+      value: Box::new(alt.clone()),
     }))));
   }
-  JSXElement {
+  JSXElementChild::JSXExprContainer(JSXExprContainer {
     span: DUMMY_SP,
-    opening: JSXOpeningElement {
-      name: JSXElementName::Ident(JINGE_IMPORT_IF.local()),
-      span: cons.span(),
-      attrs: vec![JSXAttrOrSpread::JSXAttr(JSXAttr {
-        span: test.span(),
-        name: JSXAttrName::Ident(EXPECT.clone()),
-        value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
-          span: test.span(),
-          expr: JSXExpr::Expr(test.clone()),
-        })),
-      })],
-      self_closing: false,
-      type_args: None,
-    },
-    children: vec![JSXElementChild::JSXExprContainer(JSXExprContainer {
+    expr: JSXExpr::Expr(Box::new(Expr::Object(ObjectLit {
       span: DUMMY_SP,
-      expr: JSXExpr::Expr(Box::new(Expr::Object(ObjectLit {
-        span: DUMMY_SP,
-        props: slots,
-      }))),
-    })],
-    closing: Some(JSXClosingElement {
-      name: JSXElementName::Ident(JINGE_IMPORT_IF.local()),
-      span: DUMMY_SP,
-    }),
-  }
+      props: slots,
+    }))),
+  })
 }
 
 /// 判定表达式是否是 null 或者 undefined。注意 null 是 Lit::Null，但 undefined 是 Ident 类型。
-fn is_null_undef(expr: &Expr) -> bool {
+pub fn is_null_undef(expr: &Expr) -> bool {
   match expr {
     Expr::Lit(Lit::Null(_)) => true,
     Expr::Ident(id) => JINGE_UNDEFINED.eq(&id.sym),
@@ -113,55 +87,59 @@ impl TemplateParser {
         expr.cons.clone()
       };
       if matches!(expr_result, ExprParseResult::None) {
-        self.push_expression(tpl_render_const_text(
-          Box::new(Expr::Cond(CondExpr {
+        self.push_node(Node::Text(Box::new(Expr::Cond(CondExpr {
+          span: DUMMY_SP,
+          test: expr.test.clone(),
+          alt,
+          cons,
+        }))));
+      } else {
+        self.push_node(Node::TextBind {
+          watch: expr_result,
+          value: Box::new(Expr::Cond(CondExpr {
             span: DUMMY_SP,
-            test: expr.test.clone(),
-            alt,
-            cons,
+            test: ast_create_expr_ident(JINGE_V_IDENT.ident()),
+            alt: alt.clone(),
+            cons: cons.clone(),
           })),
-          self.context.is_parent_component(),
-          self.context.root_container,
-        ));
-      } else {
-        self.push_expression(tpl_render_expr_text(
-          expr_result,
-          Box::new(Expr::Cond(CondExpr {
+          expr: Box::new(Expr::Cond(CondExpr {
             span: DUMMY_SP,
-            test: ast_create_expr_ident(JINGE_V_IDENT.clone()),
+            test: expr.test.clone(),
             alt,
             cons,
           })),
-          self.context.is_parent_component(),
-          self.context.root_container,
-        ));
+        });
       }
       return; // important to return !!
     }
 
     // 如果是 alt 和 cons 是非常量的表达式，比如 `this.submitting ? <p>Submitting</p> : <span>SUBMIT</span>`，
     // 转换为 `If` 组件：```<If expect={this.submitting}>{{true: <p>Submitting</p>, false: <span>SUBMIT</p> }}</If>```
-    let if_component = gen_if_component(
+    self.parse_if(
       &expr.test,
       if is_alt_null_undef {
         None
       } else {
-        Some(&expr.alt)
+        Some(expr.alt.as_ref())
       },
       if is_cons_null_undef {
         None
       } else {
-        Some(&expr.cons)
+        Some(expr.cons.as_ref())
       },
     );
-    self.parse_component_element(&JINGE_IMPORT_IF.local(), &if_component);
   }
 
   pub fn parse_logic_and_expr(&mut self, expr: &BinExpr) {
     if is_null_undef(&expr.right) {
       return;
     }
-    let if_component = gen_if_component(&expr.left, None, Some(&expr.right));
-    self.parse_component_element(&JINGE_IMPORT_IF.local(), &if_component);
+    self.parse_if(&expr.left, None, Some(expr.right.as_ref()));
+  }
+
+  fn parse_if(&mut self, test: &Expr, alt: Option<&Expr>, cons: Option<&Expr>) {
+    let expect = parse_value(test);
+    let slots = self.parse_component_slots(&[gen_if_slots(alt, cons)]);
+    self.push_node(Node::If(If { expect, slots }));
   }
 }
@@ -0,0 +1,125 @@
+use hashbrown::HashMap;
+use swc_core::atoms::Atom;
+use swc_core::common::comments::SingleThreadedComments;
+use swc_core::common::{BytePos, Span};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{noop_visit_type, Visit, VisitWith};
+
+use crate::common::JINGE_CHILDREN;
+
+const CUSTOM_ELEMENT_COMMENT_PREFIX: &str = "jinge-custom-element";
+
+/// 组件声明前的 `// jinge-custom-element x-card` 注释，表示同时把该组件注册为 Custom Element，
+/// 以便在非 jinge 的页面（比如 CMS、jQuery 后台）中通过 `<x-card>` 使用。
+pub struct CustomElementPragma {
+  pub tag: Atom,
+  pub span: Span,
+}
+
+/// 源码中全部的 `jinge-custom-element` 注释，以注释之后的语句的起始位置为 key。
+#[derive(Default)]
+pub struct CustomElementPragmas(HashMap<BytePos, CustomElementPragma>);
+impl CustomElementPragmas {
+  pub fn collect(comments: &SingleThreadedComments) -> Self {
+    let (leading, _) = comments.borrow_all();
+    let mut pragmas = HashMap::new();
+    leading.iter().for_each(|(pos, cmts)| {
+      cmts.iter().for_each(|c| {
+        let Some(rest) = c.text.trim().strip_prefix(CUSTOM_ELEMENT_COMMENT_PREFIX) else {
+          return;
+        };
+        pragmas.insert(
+          *pos,
+          CustomElementPragma {
+            tag: rest.trim().into(),
+            span: c.span,
+          },
+        );
+      });
+    });
+    Self(pragmas)
+  }
+  pub fn take(&mut self, pos: BytePos) -> Option<CustomElementPragma> {
+    self.0.remove(&pos)
+  }
+}
+
+/// Custom Element 的名称必须以小写字母打头，包含 `-`，并且不能有大写字母。
+pub fn is_valid_custom_element_name(tag: &str) -> bool {
+  tag.starts_with(|c: char| c.is_ascii_lowercase())
+    && tag.contains('-')
+    && tag
+      .chars()
+      .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '.' | '_'))
+}
+
+/// 函数组件中用到的 props，用于生成 Custom Element 的属性、事件和 slot 映射：
+/// `props.title` 是属性，`props.onChange` 是事件，`props.children` 和 `props.children.header` 是 slot。
+#[derive(Default)]
+pub struct PropsUsage {
+  pub attrs: Vec<Atom>,
+  pub events: Vec<Atom>,
+  pub slots: Vec<Atom>,
+  pub default_slot: bool,
+}
+impl PropsUsage {
+  pub fn collect<N: VisitWith<PropsUsageVisitor>>(props_arg: Atom, node: &N) -> Self {
+    let mut v = PropsUsageVisitor {
+      props_arg,
+      usage: Default::default(),
+    };
+    node.visit_with(&mut v);
+    v.usage
+  }
+}
+
+pub struct PropsUsageVisitor {
+  props_arg: Atom,
+  usage: PropsUsage,
+}
+impl PropsUsageVisitor {
+  /// 如果 m 是 `props.xx`，返回 xx
+  fn props_member<'a>(&self, m: &'a MemberExpr) -> Option<&'a Atom> {
+    match (m.obj.as_ref(), &m.prop) {
+      (Expr::Ident(obj), MemberProp::Ident(prop)) if obj.sym == self.props_arg => Some(&prop.sym),
+      _ => None,
+    }
+  }
+}
+impl Visit for PropsUsageVisitor {
+  noop_visit_type!();
+  fn visit_member_expr(&mut self, n: &MemberExpr) {
+    // props.children.header
+    if matches!(n.obj.as_ref(), Expr::Member(obj) if self.props_member(obj).is_some_and(|p| JINGE_CHILDREN.eq(p)))
+    {
+      if let MemberProp::Ident(slot) = &n.prop {
+        push_unique(&mut self.usage.slots, &slot.sym);
+      }
+      return;
+    }
+    let Some(prop) = self.props_member(n).cloned() else {
+      n.visit_children_with(self);
+      return;
+    };
+    if JINGE_CHILDREN.eq(&prop) {
+      self.usage.default_slot = true;
+    } else if is_event_prop(&prop) {
+      push_unique(&mut self.usage.events, &prop);
+    } else {
+      push_unique(&mut self.usage.attrs, &prop);
+    }
+  }
+}
+
+fn push_unique(list: &mut Vec<Atom>, v: &Atom) {
+  if !list.contains(v) {
+    list.push(v.clone());
+  }
+}
+
+/// `onChange` 这样 on 之后紧跟大写字母的属性是事件。
+fn is_event_prop(prop: &str) -> bool {
+  prop
+    .strip_prefix("on")
+    .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+}
@@ -11,20 +11,22 @@ use swc_core::{
 };
 use swc_ecma_visit::VisitWith;
 
+use super::props::{as_prop_default, with_default};
 use crate::{
   ast::{
     ast_create_arg_expr, ast_create_expr_arrow_fn, ast_create_expr_call, ast_create_expr_ident,
     ast_create_expr_this,
   },
   common::{
-    emit_error, JINGE_IMPORT_DYM_PATH_WATCHER, JINGE_IMPORT_EXPR_WATCHER, JINGE_IMPORT_PATH_WATCHER,
+    emit_error, private_ident, DiagCode, JINGE_IMPORT_DYM_PATH_WATCHER, JINGE_IMPORT_EXPR_WATCHER,
+    JINGE_IMPORT_PATH_WATCHER,
   },
 };
 
 enum Root {
   None,
   This,
-  Id(Atom),
+  Id(Ident),
 }
 #[derive(Debug)]
 pub struct SimpleExprParseResult {
@@ -49,6 +51,8 @@ pub struct ExprVisitor {
   level: usize,
   simple_result: Option<SimpleExprParseResult>,
   exclude_roots: ExcludeRoots,
+  /// 是否有 member 表达式的 object 是带默认值的 props 属性，参看 [MemberExprVisitor::default_base]
+  default_base: bool,
 }
 
 impl ExprVisitor {
@@ -65,6 +69,7 @@ impl ExprVisitor {
       expressions: vec![],
       simple_result: None,
       exclude_roots: watch_exclude_roots,
+      default_base: false,
     }
   }
 
@@ -148,7 +153,7 @@ impl ExprVisitor {
       return None;
     }
     // 如果表达式整个是一个 MemberExpr，则不需要使用 ExprWatcher 进一步封装。
-    if matches!(expr, Expr::Member(_)) {
+    if matches!(expr, Expr::Member(_)) && !self.default_base {
       self.expressions.pop()
     } else {
       Some(self.covert(expr))
@@ -160,6 +165,12 @@ impl Visit for ExprVisitor {
     if self.no_watch {
       return;
     }
+    // 带默认值的 props 属性只需要监听一次 member 表达式，参看 [as_prop_default]
+    if let Some((member, default_value)) = as_prop_default(node) {
+      self.visit_member_expr(member);
+      self.visit_expr(default_value);
+      return;
+    }
     node.visit_children_with(self);
   }
   fn visit_member_expr(&mut self, node: &MemberExpr) {
@@ -173,6 +184,7 @@ impl Visit for ExprVisitor {
       self.no_watch = true;
       return;
     }
+    self.default_base |= mem_parser.default_base;
 
     let mut args: Vec<ExprOrSpread> = Vec::with_capacity(mem_parser.path.len() + 2);
     let mut is_this = false;
@@ -181,7 +193,7 @@ impl Visit for ExprVisitor {
         is_this = true;
         ast_create_expr_this()
       }
-      Root::Id(id) => Box::new(Expr::Ident(Ident::from(id))),
+      Root::Id(id) => Box::new(Expr::Ident(id)),
       Root::None => unreachable!(),
     };
     let watch_path = Box::new(Expr::Array(ArrayLit {
@@ -193,7 +205,11 @@ impl Visit for ExprVisitor {
         .collect(),
     }));
 
-    if self.level == 0 && !mem_parser.computed && self.expressions.is_empty() {
+    if self.level == 0
+      && !mem_parser.computed
+      && !mem_parser.default_base
+      && self.expressions.is_empty()
+    {
       // 如果没有 computed 属性，且是第一层的第一个 member expr，则先假设整个表达式都只有这一个 member expr 保存 vm 和 path
       // 待表达式整体全部 visit 结束后，再根据最终的结果看是否使用这个 simple result 作为返回数据。
       self.simple_result = Some(SimpleExprParseResult {
@@ -234,7 +250,7 @@ impl MemberExprReplaceVisitor {
     }
   }
   fn get_alias_ident(&mut self) -> Expr {
-    let id = Ident::from(format!("a{}", self.count));
+    let id = private_ident(format!("a{}", self.count).into());
     let p = Pat::Ident(BindingIdent {
       id: id.clone(),
       type_ann: None,
@@ -246,20 +262,52 @@ impl MemberExprReplaceVisitor {
 }
 impl VisitMut for MemberExprReplaceVisitor {
   fn visit_mut_expr(&mut self, node: &mut Expr) {
+    if let Some((member, default_value)) = as_prop_default(node) {
+      // `props$jg$.a === undefined ? 10 : props$jg$.a` 中的两个 member 表达式使用同一个参数。
+      let base = has_default_base(member);
+      let alias = self.get_alias_ident();
+      let mut default_value = Box::new(default_value.clone());
+      default_value.visit_mut_with(self);
+      if !base {
+        *node = *with_default(Box::new(alias), default_value);
+      }
+      return;
+    }
     match node {
       Expr::OptChain(oc) => {
-        if let OptChainBase::Member(_) = oc.base.as_ref() {
-          *node = self.get_alias_ident()
+        if let OptChainBase::Member(m) = oc.base.as_ref() {
+          if has_default_base(m) {
+            self.get_alias_ident();
+          } else {
+            *node = self.get_alias_ident()
+          }
         } else {
           // ignore
         }
       }
+      Expr::Member(m) if has_default_base(m) => {
+        self.get_alias_ident();
+      }
       Expr::Member(_) => *node = self.get_alias_ident(),
       _ => node.visit_mut_children_with(self),
     }
   }
 }
 
+/// member 表达式的 object 是否是带默认值的 props 属性，例如 `(props$jg$.list === undefined ? [] : props$jg$.list).length`。
+/// 监听的路径是 `props$jg$.list.length`，但它的值在使用默认值时是错误的，因此参数只用于触发更新，原始表达式保持不变，
+/// 在回调中直接求值。
+fn has_default_base(node: &MemberExpr) -> bool {
+  match node.obj.as_ref() {
+    Expr::Member(m) => has_default_base(m),
+    Expr::OptChain(oc) => match oc.base.as_ref() {
+      OptChainBase::Member(m) => has_default_base(m),
+      OptChainBase::Call(_) => false,
+    },
+    obj => as_prop_default(obj).is_some(),
+  }
+}
+
 struct MemberExprVisitor {
   root: Root,
   path: Vec<Box<Expr>>,
@@ -268,6 +316,8 @@ struct MemberExprVisitor {
   level: usize,
   computed: bool,
   exclude_roots: ExcludeRoots,
+  /// object 是带默认值的 props 属性，参看 [has_default_base]
+  default_base: bool,
 }
 impl MemberExprVisitor {
   fn new(level: usize, exclude_roots: ExcludeRoots) -> Self {
@@ -279,6 +329,7 @@ impl MemberExprVisitor {
       meet_private: false,
       computed: false,
       exclude_roots,
+      default_base: false,
     }
   }
 }
@@ -293,10 +344,10 @@ impl Visit for MemberExprVisitor {
           if exclude_roots.contains(&id.sym) {
             self.meet_private = true;
           } else {
-            self.root = Root::Id(id.sym.clone());
+            self.root = Root::Id(id.clone());
           }
         } else {
-          self.root = Root::Id(id.sym.clone());
+          self.root = Root::Id(id.clone());
         }
       }
       Expr::Member(expr) => {
@@ -304,10 +355,7 @@ impl Visit for MemberExprVisitor {
       }
       Expr::OptChain(expr) => match expr.base.as_ref() {
         OptChainBase::Call(call) => {
-          emit_error(
-            call.span(),
-            "暂不支持该 Call 表达式作为 Member Expr 的 object",
-          );
+          emit_error(call.span(), DiagCode::CallAsMemberObject);
           self.meet_error = true;
         }
         OptChainBase::Member(mem) => {
@@ -318,15 +366,17 @@ impl Visit for MemberExprVisitor {
         // TODO: 支持形如 `state.a().b.c` 这样的，函数调用的结果作为 ViewModel 进一步取其上的属性。
         // 这种情况会比较复杂，因为 watch 的目标是动态的，需要用 PathWatcher/DymPathWatcher/ExprWatcher 之外更复杂的 watcher 机制来支持。
         // 后续如果这种情况是刚需再考虑支持。
-        emit_error(
-          call.span(),
-          "暂不支持该 Call 表达式作为 Member Expr 的 object",
-        );
+        emit_error(call.span(), DiagCode::CallAsMemberObject);
         self.meet_error = true;
       }
-      _ => {
-        emit_error(node.obj.span(), "不支持该类型的表达式");
-        self.meet_error = true;
+      obj => {
+        if let Some((member, _)) = as_prop_default(obj) {
+          self.default_base = true;
+          self.visit_member_expr(member);
+        } else {
+          emit_error(obj.span(), DiagCode::UnsupportedExpr);
+          self.meet_error = true;
+        }
       }
     }
     if self.meet_error || self.meet_private {
@@ -348,7 +398,7 @@ impl Visit for MemberExprVisitor {
             Lit::Str(_) | Lit::Num(_) => self.path.push(Box::new(Expr::Lit(v.clone()))),
             _ => {
               self.meet_error = true;
-              emit_error(v.span(), "不支持该常量作为属性");
+              emit_error(v.span(), DiagCode::UnsupportedLitProp);
             }
           },
 
@@ -360,7 +410,8 @@ impl Visit for MemberExprVisitor {
               self.computed = true;
               self.path.push(result);
             } else {
-              todo!("xxx")
+              // 计算属性本身不需要监听，比如 `state.list[idx]` 中的 idx 是普通的局部变量，直接作为固定的路径。
+              self.path.push(Box::new(expr.clone()));
             }
           }
         }
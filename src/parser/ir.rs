@@ -0,0 +1,189 @@
+use swc_core::atoms::Atom;
+use swc_core::ecma::ast::{Expr, Ident, IdentName, Pat, PropName};
+
+use crate::common::{DiagCode, JINGE_FOREIGN_OBJECT, JINGE_MATH, JINGE_SVG};
+
+use super::Parent;
+
+pub use super::expr::ExprParseResult;
+
+/// 属性、参数等的值。
+pub enum Value {
+  /// 不需要监听变更的值，例如 `a="hello"`、`b={45 + _someVar.o}`、`onClick={() => {}}`
+  Const(Box<Expr>),
+  /// 需要监听变更的表达式绑定，例如 `a={this.title}`。expr 是原始表达式，只渲染一次的后端（例如 SSR）直接求值。
+  Bind {
+    expr: Box<Expr>,
+    watch: ExprParseResult,
+  },
+}
+impl Value {
+  /// 值对应的原始表达式。
+  pub fn into_expr(self) -> Box<Expr> {
+    match self {
+      Value::Const(e) => e,
+      Value::Bind { expr, .. } => expr,
+    }
+  }
+}
+
+/// html 元素上的事件，例如 `<div onClick={(evt) => {}}></div>`
+pub struct Event {
+  pub event_name: Atom,
+  pub event_handler: Box<Expr>,
+  pub capture: bool,
+}
+
+/// 元素所在的命名空间，决定运行时使用 createElement 还是 createElementNS 创建元素。
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Namespace {
+  #[default]
+  Html,
+  Svg,
+  MathMl,
+}
+impl Namespace {
+  /// tag 元素的命名空间：`<svg>` 和 `<math>` 开始新的命名空间，其余元素和父元素相同。
+  pub fn of(tag: &Atom, parent: Namespace) -> Self {
+    if JINGE_SVG.eq(tag) {
+      Namespace::Svg
+    } else if JINGE_MATH.eq(tag) {
+      Namespace::MathMl
+    } else {
+      parent
+    }
+  }
+  /// 该命名空间下 tag 元素的子节点的 Parent。svg 的 `<foreignObject>` 中的子节点回到 html 命名空间。
+  pub fn children_parent(self, tag: &Atom) -> Parent {
+    match self {
+      Namespace::Svg if JINGE_FOREIGN_OBJECT.eq(tag) => Parent::Html,
+      Namespace::Svg => Parent::Svg,
+      Namespace::MathMl => Parent::MathMl,
+      Namespace::Html => Parent::Html,
+    }
+  }
+}
+
+/// html/svg/MathML 元素，例如 `<div class="a" onClick={...}>...</div>`
+pub struct Element {
+  pub tag: Ident,
+  pub ns: Namespace,
+  /// 属性按书写顺序排列，className 和 htmlFor 已经转换为 class 和 for
+  pub attrs: Vec<(IdentName, Value)>,
+  /// `style={{ fontSize: this.size }}` 对象写法的样式，每个 css 属性单独设置和监听。key 已经转换为 css 属性名，例如 `font-size`。
+  /// 字符串等其它写法的 style 仍然是普通属性。
+  pub style: Vec<(Atom, Value)>,
+  /// `class={["btn", { active: this.on }]}` 和 `class:active={this.on}` 中按条件切换的 class，每个 class 单独使用
+  /// `classList.toggle` 设置和监听。其中固定的 class 已经合并到 attrs 的 class 属性中。
+  pub class_toggles: Vec<(Atom, Value)>,
+  /// 多选 select 上的 `bind:value={state.tags}`。数组不能通过 value 属性设置，而是监听后由运行时的 setSelectValues 逐个设置 option 的 selected。
+  pub select_values: Option<ExprParseResult>,
+  pub events: Vec<Event>,
+  pub ref_prop: Option<Box<Expr>>,
+  pub children: Vec<Node>,
+}
+impl Element {
+  #[inline]
+  pub fn children_parent(&self) -> Parent {
+    self.ns.children_parent(&self.tag.sym)
+  }
+}
+
+/// 组件的 Slot 定义。默认 Slot 的 name 为空。
+pub struct Slot {
+  pub name: Atom,
+  /// Slot 函数的参数，例如 `{(vm) => <span>{vm.name}</span>}` 中的 vm
+  pub params: Vec<Pat>,
+  pub children: Vec<Node>,
+}
+impl Slot {
+  pub fn new(name: Atom) -> Self {
+    Self {
+      name,
+      params: vec![],
+      children: vec![],
+    }
+  }
+}
+
+/// 组件元素，例如 `<A a={this.b} ref="x">{...}</A>`
+pub struct Component {
+  /// 组件的引用，例如 `A`，或者成员表达式 `UI.Button`、`this.Item`
+  pub tag: Box<Expr>,
+  /// 组件属性，事件属性（`onClick`）也作为普通属性传递
+  pub attrs: Vec<(IdentName, Value)>,
+  /// `<A {...state} />` 透传的 props，有 spread 时 attrs 一定为空
+  pub spread: Option<Ident>,
+  pub ref_prop: Option<Box<Expr>>,
+  /// 第 0 个是默认 Slot。有命名 Slot 时，默认 Slot 一定为空。
+  pub slots: Vec<Slot>,
+}
+
+/// 动态组件，例如 `<Dynamic component={props.as} class="box" onClick={...}>{...}</Dynamic>`。
+/// component 是组件或者 html 标签名（字符串），变更时运行时销毁当前渲染的组件或元素，
+/// 再使用同一份属性、事件和 Slot 渲染新的组件或元素。
+pub struct Dynamic {
+  pub component: Value,
+  /// component 以外的属性，事件属性（`onClick`）也作为普通属性传递，参看 [Component::attrs]
+  pub attrs: Vec<(IdentName, Value)>,
+  pub ref_prop: Option<Box<Expr>>,
+  pub slots: Vec<Slot>,
+}
+
+/// 渲染父组件传递的 Slot，例如 `{props.children}`、`{props.children.header({ a: 1 })}`
+pub struct RenderSlot {
+  /// None 表示默认 Slot
+  pub name: Option<Atom>,
+  /// 传递给 Slot 函数的参数
+  pub args: Vec<(PropName, Value)>,
+  /// `props.children({ ...vm })` 透传的参数，有 spread 时 args 一定为空
+  pub spread: Option<Ident>,
+}
+
+/// 条件渲染，例如 `{test ? <A /> : <B />}`、`{test && <A />}`，
+/// 和 `<If expect={test}>{{ true: ..., false: ... }}</If>` 等价，slots 和 [Component::slots] 相同。
+pub struct If {
+  pub expect: Value,
+  pub slots: Vec<Slot>,
+}
+
+/// 列表循环，例如 `{list.map((item) => <li>{item}</li>)}`，和 `<For loop={list} keyFn={...}>{(each) => ...}</For>` 等价。
+pub struct For {
+  pub looop: Value,
+  pub key_fn: Option<Value>,
+  /// 循环体，默认 Slot 的参数是 each 变量（map 回调参数已经替换为 `each.data` 和 `each.index`），参看 [Component::slots]
+  pub slots: Vec<Slot>,
+}
+
+/// 国际化多语言文本 `t("...", params)`
+pub struct Intl {
+  pub key: Atom,
+  /// 参数包含 jsx 或函数时是富文本
+  pub is_rich_text: bool,
+  pub params: Vec<(PropName, Value)>,
+  /// 不是生产模式时需要传递默认文本
+  pub default_text: Option<Atom>,
+}
+
+/// 模板的中间表示（IR）节点。TemplateParser 把组件 return 的 jsx 解析为 IR 节点树，再由具体的后端（参看 crate::codegen）生成代码。
+/// IR 只描述模板的结构和数据绑定（哪些是常量，哪些需要监听变更），不关心最终生成的渲染代码的形式。
+pub enum Node {
+  Element(Element),
+  Component(Component),
+  Dynamic(Dynamic),
+  /// 不需要监听的文本，例如 jsx 文本 `hello`、`{45}`、`{_someVar.o}`
+  Text(Box<Expr>),
+  /// 绑定了表达式的文本，表达式变更时更新文本内容。value 是以 `v` 为参数计算文本的表达式，例如 `v` 或 `v ? "a" : "b"`，
+  /// expr 是直接计算文本的原始表达式，例如 `this.title` 或 `this.ok ? "a" : "b"`
+  TextBind {
+    watch: ExprParseResult,
+    value: Box<Expr>,
+    expr: Box<Expr>,
+  },
+  RenderSlot(RenderSlot),
+  If(If),
+  For(For),
+  Intl(Intl),
+  /// 编译出错的位置，运行时抛出异常
+  Error(DiagCode),
+}
@@ -0,0 +1,387 @@
+use hashbrown::{HashMap, HashSet};
+use swc_core::atoms::Atom;
+use swc_core::common::Span;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{
+  noop_visit_mut_type, noop_visit_type, Visit, VisitMut, VisitMutWith, VisitWith,
+};
+
+use crate::codegen::tpl::tpl_error_stub;
+use crate::common::{emit_error_is_fatal, emit_error_with_args, DiagCode};
+
+use super::has_jsx;
+
+/// 组件函数体内绑定了 jsx 的 const 局部变量，例如：
+/// ```tsx
+/// const header = <h1>{props.title}</h1>;
+/// return <div>{header}</div>;
+/// ```
+/// 删除局部变量的声明，并把 jsx 内联到模板中使用该变量的位置，这样 TemplateParser 会把它和模板一起编译为渲染片段。
+/// 局部变量只能在模板中作为子节点（或者子节点的条件表达式分支）使用，其它用法会报错。
+/// 函数体内 if 等语句块中声明的局部变量同样会内联，嵌套函数中的不会。
+///
+/// 内联后 jsx 中的表达式在使用的位置求值，而不是在声明的位置，因此：
+/// - 声明之后又在函数体中被重新赋值的变量，内联会改变渲染的值，报 [DiagCode::JsxLocalCaptureAssign] 错误。
+///   嵌套函数（例如事件处理函数）中的赋值在渲染之后才执行，不会报错。
+/// - 每一处使用都会内联一份独立的 jsx，渲染出各自的 dom 节点，其中的函数调用等有副作用的表达式在每一处都会执行一次，
+///   使用多次时报 [DiagCode::JsxLocalSideEffect] 警告。
+///
+/// 只能在确认函数是组件之后调用（参看 [jsx_local_ids]），普通函数中的 jsx 局部变量保持原样。
+pub fn inline_jsx_locals(body: &mut BlockStmt) {
+  let mut v = JsxLocalReplaceVisitor {
+    locals: HashMap::new(),
+    fn_level: 0,
+  };
+  body.visit_mut_with(&mut v);
+  let mut locals: Vec<_> = v
+    .locals
+    .into_values()
+    .filter(|l| l.side_effect && l.uses > 1)
+    .collect();
+  locals.sort_by_key(|l| l.span.lo);
+  for l in locals {
+    emit_error_with_args(l.span, DiagCode::JsxLocalSideEffect, &[&l.name]);
+  }
+}
+
+/// 函数体内（包括 if 等语句块，不包括嵌套函数）绑定了 jsx 的局部变量。`const header = <h1 />; return header;` 这样的函数也是组件，
+/// 但在 inline_jsx_locals 之前 return 的还不是 jsx，需要通过这些变量判定。
+pub fn jsx_local_ids(body: &BlockStmt) -> Vec<Id> {
+  let mut v = JsxLocalIdsVisitor { ids: vec![] };
+  body.visit_children_with(&mut v);
+  v.ids
+}
+
+struct JsxLocalIdsVisitor {
+  ids: Vec<Id>,
+}
+impl Visit for JsxLocalIdsVisitor {
+  noop_visit_type!();
+  fn visit_var_declarator(&mut self, d: &VarDeclarator) {
+    if let (Pat::Ident(id), Some(init)) = (&d.name, d.init.as_deref()) {
+      if has_jsx(init) {
+        self.ids.push(id.to_id());
+      }
+    }
+  }
+  fn visit_function(&mut self, _: &Function) {}
+  fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+  fn visit_class(&mut self, _: &Class) {}
+}
+
+/// 内联到模板中的 jsx 局部变量，参看 [inline_jsx_locals]。
+struct JsxLocal {
+  name: Atom,
+  jsx: Box<Expr>,
+  /// 声明的位置
+  span: Span,
+  /// jsx 中引用的变量
+  captures: HashSet<Id>,
+  /// jsx 中是否有在渲染时执行的副作用表达式，参看 [SideEffectFinder]
+  side_effect: bool,
+  /// 在模板中使用的次数
+  uses: usize,
+}
+
+/// 收集 jsx 中引用的变量。
+struct CaptureCollector {
+  ids: HashSet<Id>,
+}
+impl Visit for CaptureCollector {
+  noop_visit_type!();
+  fn visit_ident(&mut self, n: &Ident) {
+    self.ids.insert(n.to_id());
+  }
+}
+
+/// 查找渲染时会执行的有副作用的表达式：赋值、自增自减、delete、await、yield、new 和函数调用。
+/// 列表渲染的 `list.map(...)` 由模板编译，不是副作用。嵌套函数（事件处理函数等）不在渲染时执行，不需要查找。
+struct SideEffectFinder {
+  found: bool,
+}
+impl Visit for SideEffectFinder {
+  noop_visit_type!();
+  fn visit_expr(&mut self, n: &Expr) {
+    match n {
+      Expr::Assign(_) | Expr::Update(_) | Expr::Await(_) | Expr::Yield(_) | Expr::New(_) => {
+        self.found = true
+      }
+      Expr::Unary(e) if e.op == UnaryOp::Delete => self.found = true,
+      Expr::Call(call) => match &call.callee {
+        Callee::Expr(callee) if matches!(callee.as_ref(), Expr::Member(m) if matches!(&m.prop, MemberProp::Ident(p) if p.sym == "map")) => {
+          n.visit_children_with(self)
+        }
+        _ => self.found = true,
+      },
+      _ => n.visit_children_with(self),
+    }
+  }
+  fn visit_function(&mut self, _: &Function) {}
+  fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+}
+
+struct JsxLocalReplaceVisitor {
+  locals: HashMap<Id, JsxLocal>,
+  /// 嵌套函数的层级，嵌套函数中的 return 不是组件的模板。
+  fn_level: usize,
+}
+impl JsxLocalReplaceVisitor {
+  /// 处理语句列表中的变量声明：删除 jsx 局部变量的声明并记录下来，其它语句替换其中使用的 jsx 局部变量。
+  fn inline_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+    stmts.retain_mut(|stmt| {
+      let Stmt::Decl(Decl::Var(decl)) = stmt else {
+        stmt.visit_mut_with(self);
+        return true;
+      };
+      let kind = decl.kind;
+      decl.decls.retain_mut(|d| {
+        let (Pat::Ident(id), Some(init)) = (&d.name, d.init.as_mut()) else {
+          d.visit_mut_with(self);
+          return true;
+        };
+        if !has_jsx(init.as_ref()) {
+          d.visit_mut_with(self);
+          return true;
+        }
+        if kind != VarDeclKind::Const {
+          // 未被编译的 jsx 会再被报告为 JG2011，因此替换为抛错桩。
+          if emit_error_is_fatal(d.span, DiagCode::JsxLocalNotConst) {
+            *init = tpl_error_stub(DiagCode::JsxLocalNotConst);
+          }
+          return true;
+        }
+        // 先替换 jsx 中引用的其它局部变量，支持 `const a = <b />; const c = <div>{a}</div>;`
+        self.replace_template_expr(init);
+        let mut captures = CaptureCollector {
+          ids: HashSet::new(),
+        };
+        init.visit_with(&mut captures);
+        let mut side_effect = SideEffectFinder { found: false };
+        init.visit_with(&mut side_effect);
+        self.locals.insert(
+          id.to_id(),
+          JsxLocal {
+            name: id.sym.clone(),
+            jsx: init.clone(),
+            span: d.span,
+            captures: captures.ids,
+            side_effect: side_effect.found,
+            uses: 0,
+          },
+        );
+        false
+      });
+      !decl.decls.is_empty()
+    });
+  }
+  /// 函数体中给 jsx 局部变量引用的变量赋值，参看 [inline_jsx_locals]。
+  fn check_assign(&self, id: &Ident) {
+    if self.fn_level > 0 {
+      return;
+    }
+    let id_key = id.to_id();
+    let mut names: Vec<_> = self
+      .locals
+      .values()
+      .filter(|l| l.captures.contains(&id_key))
+      .map(|l| (l.span.lo, &l.name))
+      .collect();
+    names.sort();
+    for (_, name) in names {
+      emit_error_with_args(id.span, DiagCode::JsxLocalCaptureAssign, &[name, &id.sym]);
+    }
+  }
+  /// 模板位置的表达式：可以直接替换为局部变量对应的 jsx。条件表达式的分支和 `&&` 的右侧也是模板位置。
+  fn replace_template_expr(&mut self, expr: &mut Box<Expr>) {
+    match expr.as_mut() {
+      Expr::Ident(id) => {
+        if let Some(local) = self.locals.get_mut(&id.to_id()) {
+          local.uses += 1;
+          *expr = local.jsx.clone();
+        }
+      }
+      Expr::Paren(e) => self.replace_template_expr(&mut e.expr),
+      Expr::Cond(e) => {
+        e.test.visit_mut_with(self);
+        self.replace_template_expr(&mut e.cons);
+        self.replace_template_expr(&mut e.alt);
+      }
+      Expr::Bin(e) if e.op == BinaryOp::LogicalAnd => {
+        e.left.visit_mut_with(self);
+        self.replace_template_expr(&mut e.right);
+      }
+      _ => expr.visit_mut_with(self),
+    }
+  }
+}
+impl VisitMut for JsxLocalReplaceVisitor {
+  noop_visit_mut_type!();
+  fn visit_mut_stmts(&mut self, n: &mut Vec<Stmt>) {
+    if self.fn_level == 0 {
+      self.inline_stmts(n);
+    } else {
+      n.visit_mut_children_with(self);
+    }
+  }
+  fn visit_mut_assign_expr(&mut self, n: &mut AssignExpr) {
+    if let AssignTarget::Simple(SimpleAssignTarget::Ident(id)) = &n.left {
+      self.check_assign(&id.id);
+    }
+    n.visit_mut_children_with(self);
+  }
+  fn visit_mut_update_expr(&mut self, n: &mut UpdateExpr) {
+    if let Expr::Ident(id) = n.arg.as_ref() {
+      self.check_assign(id);
+    }
+    n.visit_mut_children_with(self);
+  }
+  fn visit_mut_jsx_expr_container(&mut self, n: &mut JSXExprContainer) {
+    if let JSXExpr::Expr(e) = &mut n.expr {
+      self.replace_template_expr(e);
+    }
+  }
+  fn visit_mut_jsx_attr_value(&mut self, n: &mut JSXAttrValue) {
+    // 属性值不是模板位置，不能使用 jsx 局部变量。
+    if let JSXAttrValue::JSXExprContainer(c) = n {
+      c.expr.visit_mut_children_with(self);
+    } else {
+      n.visit_mut_children_with(self);
+    }
+  }
+  fn visit_mut_return_stmt(&mut self, n: &mut ReturnStmt) {
+    match &mut n.arg {
+      Some(arg) if self.fn_level == 0 => self.replace_template_expr(arg),
+      _ => n.visit_mut_children_with(self),
+    }
+  }
+  fn visit_mut_function(&mut self, n: &mut Function) {
+    self.fn_level += 1;
+    n.visit_mut_children_with(self);
+    self.fn_level -= 1;
+  }
+  fn visit_mut_arrow_expr(&mut self, n: &mut ArrowExpr) {
+    self.fn_level += 1;
+    match n.body.as_mut() {
+      // `list.map((item) => <li>{header}</li>)` 箭头函数直接返回的 jsx 也是模板位置。
+      BlockStmtOrExpr::Expr(e) if has_jsx(e.as_ref()) => self.replace_template_expr(e),
+      _ => n.visit_mut_children_with(self),
+    }
+    self.fn_level -= 1;
+  }
+  fn visit_mut_expr(&mut self, n: &mut Expr) {
+    if let Expr::Ident(id) = n {
+      if self.locals.contains_key(&id.to_id()) {
+        emit_error_with_args(id.span, DiagCode::JsxLocalUsage, &[&id.sym]);
+      }
+    } else {
+      n.visit_mut_children_with(self);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_jsx_local() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  const header = <h1>{props.title}</h1>;
+  console.log(header);
+  return <div>{header}{props.show && header}</div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .filter_map(|d| d.code.as_deref())
+      .collect();
+    assert_eq!(codes, ["JG2006"]);
+    assert!(!code.contains("const header"));
+    assert_eq!(code.matches("createEle$jg$(\"h1\"").count(), 2);
+
+    // 直接返回 jsx 局部变量的函数也是组件；不是组件的普通函数中的 jsx 局部变量保持原样。
+    let TransformOutput {
+      code,
+      diagnostics,
+      parsed_components,
+      ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function B() {
+  const body = <p>b</p>;
+  return body;
+}
+export function helper(items) {
+  let icon = <i />;
+  console.log(icon);
+  return items.length;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .filter_map(|d| d.code.as_deref())
+      .collect();
+    assert_eq!(codes, ["JG2011"]);
+    assert_eq!(parsed_components, "B");
+    assert!(!code.contains("const body"));
+    assert!(code.contains("let icon = <i/>;"));
+    assert!(code.contains("console.log(icon);"));
+
+    // 语句块中声明的局部变量同样内联；组件中的 let 只报告 JG2007；
+    // 内联会改变渲染结果（引用的变量被重新赋值）或者重复执行副作用时报告。
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function C(props) {
+  let n = 1;
+  const title = <h1 onClick={() => { n = 5; }}>{n}{format(props.a)}</h1>;
+  n = 2;
+  let icon = <i />;
+  if (props.x) {
+    const inner = <b>{props.x}</b>;
+    return <p>{inner}</p>;
+  }
+  return <div>{title}{props.show && title}</div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let spans: Vec<_> = diagnostics
+      .iter()
+      .map(|d| (d.code.as_deref().unwrap(), d.line, d.column))
+      .collect();
+    assert_eq!(
+      spans,
+      [("JG2012", 4, 3), ("JG2007", 5, 7), ("JG2013", 3, 9)]
+    );
+    assert_eq!(
+      diagnostics[0].message,
+      "JSX local `title` uses `n`, which is reassigned after the declaration. The JSX is inlined where it is used, so the assignment would change what renders"
+    );
+    assert!(!code.contains("const inner"));
+    assert!(code.contains("createEle$jg$(\"b\""));
+    assert!(code.contains("throw new Error(\"[JG2007]"));
+  }
+}
@@ -2,11 +2,13 @@ use swc_common::Spanned;
 use swc_core::{atoms::Atom, common::DUMMY_SP, ecma::ast::*};
 use swc_ecma_visit::{VisitMut, VisitMutWith};
 
-use crate::{ast::ast_create_expr_ident, parser::JINGE_LOOP_EACH_DATA};
+use crate::{ast::ast_create_expr_ident, common::private_ident, parser::JINGE_LOOP_EACH_DATA};
 
 use super::{
-  emit_error, map_key::KeyFnFindVisitor, TemplateParser, JINGE_IMPORT_FOR, JINGE_KEY_FN,
-  JINGE_LOOP, JINGE_LOOP_EACH_IDENTS, JINGE_LOOP_EACH_INDEX, JINGE_MAP,
+  attrs::parse_value,
+  ir::{For, Node},
+  map_key::KeyFnFindVisitor,
+  DiagCode, TemplateParser, JINGE_LOOP_EACH_IDENTS, JINGE_LOOP_EACH_INDEX, JINGE_MAP,
 };
 
 /// map 循环转换成 <For> 组件时，需要把 map 函数的参数，转成 <For> 组件的 Slot 函数的参数。
@@ -36,14 +38,14 @@ use super::{
 struct ReplaceVisitor {
   arg_data: Option<Atom>,
   arg_index: Option<Atom>,
-  slot_vm_name: Atom,
+  slot_vm_name: Ident,
   stack: Vec<(bool, bool)>,
 }
 #[inline]
-fn pat_to_atom(p: Option<&Pat>) -> Option<Atom> {
+fn pat_to_ident(p: Option<&Pat>) -> Option<Ident> {
   p.and_then(|p| {
     if let Pat::Ident(id) = p {
-      Some(id.sym.clone())
+      Some(id.id.clone())
     } else {
       None
     }
@@ -52,7 +54,7 @@ fn pat_to_atom(p: Option<&Pat>) -> Option<Atom> {
 
 impl ReplaceVisitor {
   #[inline]
-  fn new(arg_data: Option<Atom>, arg_index: Option<Atom>, slot_vm_name: Atom) -> Self {
+  fn new(arg_data: Option<Atom>, arg_index: Option<Atom>, slot_vm_name: Ident) -> Self {
     let overrided = (arg_data.is_none(), arg_index.is_none());
     Self {
       arg_data,
@@ -130,13 +132,13 @@ impl VisitMut for ReplaceVisitor {
       if !overrided.0 && matches!(self.arg_data, Some(ref a) if a.eq(&id.sym)) {
         *e = Expr::Member(MemberExpr {
           span: DUMMY_SP,
-          obj: ast_create_expr_ident(Ident::from(self.slot_vm_name.clone())),
+          obj: ast_create_expr_ident(self.slot_vm_name.clone()),
           prop: MemberProp::Ident(IdentName::from(JINGE_LOOP_EACH_DATA.clone())),
         });
       } else if !overrided.1 && matches!(self.arg_index, Some(ref a) if a.eq(&id.sym)) {
         *e = Expr::Member(MemberExpr {
           span: DUMMY_SP,
-          obj: ast_create_expr_ident(Ident::from(self.slot_vm_name.clone())),
+          obj: ast_create_expr_ident(self.slot_vm_name.clone()),
           prop: MemberProp::Ident(IdentName::from(JINGE_LOOP_EACH_INDEX.clone())),
         });
       }
@@ -146,44 +148,6 @@ impl VisitMut for ReplaceVisitor {
   }
 }
 
-fn gen_for_component(looop: &Box<Expr>, key: Option<Box<Expr>>, func: ArrowExpr) -> JSXElement {
-  let mut attrs = vec![JSXAttrOrSpread::JSXAttr(JSXAttr {
-    span: looop.span(),
-    name: JSXAttrName::Ident(IdentName::from(JINGE_LOOP.clone())),
-    value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
-      span: looop.span(),
-      expr: JSXExpr::Expr(looop.clone()),
-    })),
-  })];
-  if let Some(key) = key {
-    attrs.push(JSXAttrOrSpread::JSXAttr(JSXAttr {
-      span: DUMMY_SP,
-      name: JSXAttrName::Ident(IdentName::from(JINGE_KEY_FN.clone())),
-      value: Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
-        span: key.span(),
-        expr: JSXExpr::Expr(key),
-      })),
-    }))
-  };
-  JSXElement {
-    span: DUMMY_SP,
-    opening: JSXOpeningElement {
-      name: JSXElementName::Ident(JINGE_IMPORT_FOR.local()),
-      span: DUMMY_SP,
-      attrs,
-      self_closing: false,
-      type_args: None,
-    },
-    children: vec![JSXElementChild::JSXExprContainer(JSXExprContainer {
-      span: DUMMY_SP,
-      expr: JSXExpr::Expr(Box::new(Expr::Arrow(func))),
-    })],
-    closing: Some(JSXClosingElement {
-      name: JSXElementName::Ident(JINGE_IMPORT_FOR.local()),
-      span: DUMMY_SP,
-    }),
-  }
-}
 impl TemplateParser {
   /// 如果表达式是 xx.map() 调用，且参数只有一个，参数是箭头函数，则转换为 <For> 组件。
   pub fn parse_map_fn(&mut self, callee: &Expr, args: &Vec<ExprOrSpread>) -> bool {
@@ -221,24 +185,27 @@ impl TemplateParser {
 
     let mut func = match arg0.expr.as_ref() {
       Expr::Arrow(e) => e.clone(),
-      Expr::Fn(func) => {
-        emit_error(func.span(), "告警：map 函数的参数请使用箭头函数！");
-        return false;
-      }
+      // 默认是警告，此时按普通表达式渲染 map 的结果；提升为 error 时才放置出错节点。
+      Expr::Fn(func) => return self.emit_error_placeholder(func.span(), DiagCode::MapFnCallback),
       _ => {
         return false;
       }
     };
 
     // 一般情况下，map 嵌套不会太多。小于 JINGE_LOOP_EACH_IDENTS.len() 层直接用预置好的 Atom，否则才用 format! 动态拼接。
-    let slot_vm_name = JINGE_LOOP_EACH_IDENTS
-      .get(self.map_loop_level)
-      .map(|v| v.clone())
-      .unwrap_or_else(|| Atom::from(format!("each$jg${}", self.map_loop_level)));
-    let arg_data = pat_to_atom(func.params.get(0));
-    let arg_index = pat_to_atom(func.params.get(1));
-    let mut replace_visitor =
-      ReplaceVisitor::new(arg_data.clone(), arg_index.clone(), slot_vm_name.clone());
+    let slot_vm_name = private_ident(
+      JINGE_LOOP_EACH_IDENTS
+        .get(self.map_loop_level)
+        .map(|v| v.clone())
+        .unwrap_or_else(|| Atom::from(format!("each$jg${}", self.map_loop_level))),
+    );
+    let arg_data = pat_to_ident(func.params.get(0));
+    let arg_index = pat_to_ident(func.params.get(1));
+    let mut replace_visitor = ReplaceVisitor::new(
+      arg_data.as_ref().map(|id| id.sym.clone()),
+      arg_index.as_ref().map(|id| id.sym.clone()),
+      slot_vm_name.clone(),
+    );
     func.params = vec![Pat::Ident(BindingIdent::from(slot_vm_name.clone()))];
 
     let find_key_visitor = KeyFnFindVisitor {
@@ -253,13 +220,72 @@ impl TemplateParser {
       func.body.visit_mut_children_with(&mut replace_visitor);
     }
 
-    let for_component = gen_for_component(looop, key_fn, func);
-    let tn = Ident::from(JINGE_IMPORT_FOR.local());
-
+    let looop = parse_value(looop);
+    let key_fn = key_fn.as_deref().map(parse_value);
     self.map_loop_level += 1;
-    self.parse_component_element(&tn, &for_component);
+    let slots =
+      self.parse_component_slots(&[JSXElementChild::JSXExprContainer(JSXExprContainer {
+        span: DUMMY_SP,
+        expr: JSXExpr::Expr(Box::new(Expr::Arrow(func))),
+      })]);
     self.map_loop_level -= 1;
+    self.push_node(Node::For(For {
+      looop,
+      key_fn,
+      slots,
+    }));
 
     true
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_map_fn_callback_severity() {
+    let src = "export function A(props) {
+  const Error = 1;
+  return <div>{props.list.map(function (x) { return x + 1; })}</div>;
+}";
+    let run = |diagnostic: DiagnosticPolicy| {
+      inner_transform(
+        "test.tsx".into(),
+        2,
+        src.into(),
+        TransformOptions {
+          diagnostic,
+          ..Default::default()
+        },
+      )
+    };
+
+    // 默认是警告，保留原有的表达式渲染，不放置抛错桩。
+    let TransformOutput {
+      code, diagnostics, ..
+    } = run(DiagnosticPolicy::new(Lang::En));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code.as_deref(), Some("JG5003"));
+    assert!(!code.contains("throw new Error"));
+
+    let mut diagnostic = DiagnosticPolicy::new(Lang::En);
+    diagnostic.rules.insert("JG5003".into(), None);
+    let TransformOutput {
+      code, diagnostics, ..
+    } = run(diagnostic);
+    assert!(diagnostics.is_empty());
+    assert!(!code.contains("throw new Error"));
+
+    // 提升为 error 时放置抛错桩，且 Error 指向全局而不是同名局部变量。
+    let mut diagnostic = DiagnosticPolicy::new(Lang::En);
+    diagnostic.warnings_as_errors = true;
+    let TransformOutput {
+      code, diagnostics, ..
+    } = run(diagnostic);
+    assert_eq!(diagnostics[0].level, "error");
+    assert!(code.contains("throw new Error(\"[JG5003]"));
+    assert!(code.contains("const Error1 = 1"));
+  }
+}
@@ -1,18 +1,16 @@
 use swc_common::Spanned;
-use swc_core::{atoms::Atom, ecma::ast::*};
+use swc_core::ecma::ast::*;
 
 use crate::ast::ast_create_expr_arrow_fn;
 
-use super::{emit_error, JINGE_KEY};
-
-const BAD_KEY_WARNING: &'static str = "警告：key 的表达式必须是 map 函数的参数或参数的属性表达式";
+use super::{emit_error, DiagCode, JINGE_KEY};
 
 /// 将 map 函数体返回的第一个有 key 属性的 jsx 元素的 key 属性的表达式，转换成 <For> 组件的 keyFn 属性。
 /// 要求 key 属性必须是 Ident 或 MemberExpr，且必须是 map 函数的第一个 data 参数或第二个 index 参数。
 ///
 pub struct KeyFnFindVisitor {
-  pub arg_data: Option<Atom>,
-  pub arg_index: Option<Atom>,
+  pub arg_data: Option<Ident>,
+  pub arg_index: Option<Ident>,
 }
 impl KeyFnFindVisitor {
   #[inline]
@@ -35,19 +33,16 @@ impl KeyFnFindVisitor {
         return None;
       }
       if self.arg_data.is_none() && self.arg_index.is_none() {
-        emit_error(
-          attr.span(),
-          "map 函数没有指定参数，因此 key 属性无法转换为 <For> 组件的 keyFn 参数。",
-        );
+        emit_error(attr.span(), DiagCode::MapKeyNoParams);
         return None;
       }
       // println!("{:?}", attr.value);
       let Some(JSXAttrValue::JSXExprContainer(expr)) = &attr.value else {
-        emit_error(attr.span(), BAD_KEY_WARNING);
+        emit_error(attr.span(), DiagCode::MapBadKey);
         return None;
       };
       let JSXExpr::Expr(expr) = &expr.expr else {
-        emit_error(expr.span(), BAD_KEY_WARNING);
+        emit_error(expr.span(), DiagCode::MapBadKey);
         return None;
       };
       match expr.as_ref() {
@@ -57,10 +52,10 @@ impl KeyFnFindVisitor {
             root = me.obj.as_ref()
           }
           if let Expr::Ident(id) = root {
-            if self.arg_data.as_ref().map(|v| id.sym.eq(v)).is_none()
-              && self.arg_index.as_ref().map(|v| id.sym.eq(v)).is_none()
+            if self.arg_data.as_ref().map(|v| id.sym.eq(&v.sym)).is_none()
+              && self.arg_index.as_ref().map(|v| id.sym.eq(&v.sym)).is_none()
             {
-              emit_error(e.span(), BAD_KEY_WARNING);
+              emit_error(e.span(), DiagCode::MapBadKey);
               return None;
             }
             Some(ast_create_expr_arrow_fn(
@@ -68,15 +63,15 @@ impl KeyFnFindVisitor {
               Box::new(BlockStmtOrExpr::Expr(expr.clone())),
             ))
           } else {
-            emit_error(e.span(), BAD_KEY_WARNING);
+            emit_error(e.span(), DiagCode::MapBadKey);
             return None;
           }
         }
         Expr::Ident(id) => {
-          if self.arg_data.as_ref().map(|v| id.sym.eq(v)).is_none()
-            && self.arg_index.as_ref().map(|v| id.sym.eq(v)).is_none()
+          if self.arg_data.as_ref().map(|v| id.sym.eq(&v.sym)).is_none()
+            && self.arg_index.as_ref().map(|v| id.sym.eq(&v.sym)).is_none()
           {
-            emit_error(expr.span(), BAD_KEY_WARNING);
+            emit_error(expr.span(), DiagCode::MapBadKey);
             return None;
           }
           Some(ast_create_expr_arrow_fn(
@@ -85,7 +80,7 @@ impl KeyFnFindVisitor {
           ))
         }
         _ => {
-          emit_error(expr.span(), BAD_KEY_WARNING);
+          emit_error(expr.span(), DiagCode::MapBadKey);
           None
         }
       }
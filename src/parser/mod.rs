@@ -1,73 +1,71 @@
 use crate::ast::*;
 use crate::common::*;
+use binding::JingeBinding;
 use expr::{ExprParseResult, ExprVisitor};
+use ir::{Namespace, Node, Slot};
+use std::rc::Rc;
 use swc_core::atoms::Atom;
-use swc_core::common::{Spanned, DUMMY_SP};
+use swc_core::common::{Span, Spanned};
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::{Visit, VisitWith};
-use tpl::*;
+
+pub use cond::is_null_undef;
 
 mod attrs;
+pub mod binding;
 mod component;
 mod cond;
+pub mod custom_element;
 mod expr;
 pub mod intl;
+pub mod ir;
 mod jsx;
+pub mod locals;
 mod map;
 mod map_key;
+pub mod props;
 mod slot;
-pub mod tpl;
 
 pub enum Parent {
   Component,
   Html,
   Svg,
+  MathMl,
 }
 
-struct Slot {
-  name: Atom,
-  params: Vec<Pat>,
-  expressions: Vec<ExprOrSpread>,
-}
-impl Slot {
-  fn new(name: Atom) -> Self {
-    Self {
-      name,
-      params: vec![],
-      expressions: vec![],
-    }
-  }
-}
 struct Context {
   // container_component_level: usize,
   root_container: bool,
   parent: Parent,
+  /// 子元素所在的命名空间。组件的 Slot 中的元素和组件所在位置的命名空间相同，例如 `<svg><G><circle /></G></svg>`。
+  ns: Namespace,
   slots: Vec<Slot>,
 }
 
 impl Context {
-  fn new(parent: Parent, root_container: bool) -> Self {
+  fn new(parent: Parent, root_container: bool, ns: Namespace) -> Self {
     Self {
       root_container,
       parent,
+      ns,
       slots: vec![Slot::new(Atom::default())], // 第 0 个 Slot 是默认 DEFAULT_SLOT
     }
   }
   #[inline]
-  pub fn is_parent_svg(&self) -> bool {
-    matches!(self.parent, Parent::Svg)
-  }
-  #[inline]
   pub fn is_parent_component(&self) -> bool {
     matches!(self.parent, Parent::Component)
   }
 }
 
+/// 模板解析器，将组件 return 的 jsx 解析为 IR 节点树（参看 [ir::Node]），代码生成参看 crate::codegen。
 pub struct TemplateParser {
   intl_type: IntlType,
   context: Context,
   stack: Vec<Context>,
   props_arg: Option<Atom>,
+  intl_t: Rc<JingeBinding>,
+  /// 从 jinge 导入的 Dynamic 组件，参看 [ir::Dynamic]
+  dynamic: Rc<JingeBinding>,
   map_loop_level: usize,
 }
 
@@ -86,90 +84,76 @@ fn has_jsx(expr: &Expr) -> bool {
 }
 
 impl TemplateParser {
-  pub fn new(props_arg: Option<Atom>, intl_type: IntlType) -> Self {
+  pub fn new(
+    props_arg: Option<Atom>,
+    intl_type: IntlType,
+    intl_t: Rc<JingeBinding>,
+    dynamic: Rc<JingeBinding>,
+  ) -> Self {
     Self {
       intl_type,
-      context: Context::new(Parent::Component, true),
+      context: Context::new(Parent::Component, true, Namespace::Html),
       stack: vec![],
       props_arg,
+      intl_t,
+      dynamic,
       map_loop_level: 0,
     }
   }
   fn push_context(&mut self, parent: Parent, root_container: bool) {
+    let ns = match parent {
+      Parent::Component => self.context.ns,
+      Parent::Html => Namespace::Html,
+      Parent::Svg => Namespace::Svg,
+      Parent::MathMl => Namespace::MathMl,
+    };
     let current_context =
-      std::mem::replace(&mut self.context, Context::new(parent, root_container));
+      std::mem::replace(&mut self.context, Context::new(parent, root_container, ns));
     self.stack.push(current_context);
   }
   fn pop_context(&mut self) -> Context {
     std::mem::replace(&mut self.context, self.stack.pop().unwrap())
   }
   #[inline]
-  /// push expression to last slot
-  fn push_expression(&mut self, e: Box<Expr>) {
-    self
-      .context
-      .slots
-      .last_mut()
-      .unwrap()
-      .expressions
-      .push(ast_create_arg_expr(e));
+  /// push node to last slot
+  fn push_node(&mut self, node: Node) {
+    self.context.slots.last_mut().unwrap().children.push(node);
   }
-  #[inline]
-  /// push spread expression to last slot
-  fn push_expression_with_spread(&mut self, e: Box<Expr>) {
-    self
-      .context
-      .slots
-      .last_mut()
-      .unwrap()
-      .expressions
-      .push(ExprOrSpread {
-        spread: Some(DUMMY_SP),
-        expr: e,
-      });
+  /// 输出错误，并在当前位置放置出错节点（参看 [Node::Error]），然后继续解析模板的其余部分。
+  /// 只有最终的严重级别是 error 时才放置出错节点，返回是否放置。诊断是警告或者被关闭时不能生成运行时抛出异常的代码，
+  /// 由调用方按原有的方式继续编译该节点。
+  fn emit_error_placeholder(&mut self, sp: Span, code: DiagCode) -> bool {
+    let fatal = emit_error_is_fatal(sp, code);
+    if fatal {
+      self.push_node(Node::Error(code));
+    }
+    fatal
   }
-  pub fn parse(&mut self, expr: &Expr) -> Option<Box<Expr>> {
+  /// 将模板解析为 IR 节点。返回 None 表示不是模板或者模板没有任何需要渲染的内容。
+  pub fn parse(&mut self, expr: &Expr) -> Option<Vec<Node>> {
     if has_jsx(expr) || matches!(expr, Expr::Lit(_)) {
       self.visit_expr(expr);
     } else {
       return None;
     }
     assert_eq!(self.context.slots.len(), 1);
-    let elems: Vec<Option<ExprOrSpread>> = self
-      .context
-      .slots
-      .pop()
-      .unwrap()
-      .expressions
-      .into_iter()
-      .map(|e| Some(e))
-      .collect();
-    if elems.is_empty() {
+    let slot = self.context.slots.pop().unwrap();
+    if slot.children.is_empty() {
       None
     } else {
-      Some(Box::new(Expr::Array(ArrayLit {
-        span: DUMMY_SP,
-        elems,
-      })))
+      Some(slot.children)
     }
   }
   fn parse_expr(&mut self, expr: &Expr) {
     let expr_result = ExprVisitor::new().parse(expr);
     // println!("{:#?}", expr);
     match expr_result {
-      ExprParseResult::None => self.push_expression(tpl_render_const_text(
-        Box::new(expr.clone()),
-        self.context.is_parent_component(),
-        self.context.root_container,
-      )),
-      _ => {
-        self.push_expression(tpl_render_expr_text(
-          expr_result,
-          ast_create_expr_ident(JINGE_V_IDENT.clone()),
-          self.context.is_parent_component(),
-          self.context.root_container,
-        ));
-      }
+      ExprParseResult::None => self.push_node(Node::Text(Box::new(expr.clone()))),
+      _ => self.push_node(Node::TextBind {
+        watch: expr_result,
+        value: ast_create_expr_ident(JINGE_V_IDENT.ident()),
+        expr: Box::new(expr.clone()),
+      }),
     }
   }
   fn parse_mem(&mut self, parent_expr: &Expr, expr: &MemberExpr) {
@@ -205,7 +189,7 @@ impl Visit for TemplateParser {
         f.visit_children_with(self);
       }
       Expr::JSXMember(_) | Expr::JSXNamespacedName(_) => {
-        emit_error(expr_node.span(), "不支持的 jsx 格式")
+        self.emit_error_placeholder(expr_node.span(), DiagCode::UnsupportedJsx);
       }
 
       Expr::Cond(e) => {
@@ -220,28 +204,22 @@ impl Visit for TemplateParser {
       }
 
       Expr::Fn(f) => {
-        emit_error(f.span(), "tsx 中不支持函数，如果是定义 Slot 请使用箭头函数");
+        self.emit_error_placeholder(f.span(), DiagCode::FunctionInTemplate);
       }
       Expr::Arrow(expr) => {
         if !self.context.is_parent_component() || self.context.root_container {
-          emit_error(expr.span(), "Slot 定义必须位于组件下");
+          self.emit_error_placeholder(expr.span(), DiagCode::SlotOutsideComponent);
           return;
         }
         match &*expr.body {
           BlockStmtOrExpr::BlockStmt(_) => {
-            emit_error(
-              expr.span(),
-              "使用箭头函数定义默认 Slot 时必须直接在箭头后返回值",
-            );
+            self.emit_error_placeholder(expr.span(), DiagCode::SlotArrowBody);
           }
           BlockStmtOrExpr::Expr(e) => {
             if !expr.params.is_empty() {
               expr.params.iter().any(|par| {
                 if !matches!(par, Pat::Ident(_)) {
-                  emit_error(
-                    par.span(),
-                    "警告：slot 函数的参数不要使用解构的写法，会导致数据的绑定失效。",
-                  );
+                  emit_error(par.span(), DiagCode::SlotParamDestructure);
                   true
                 } else {
                   false
@@ -263,12 +241,12 @@ impl Visit for TemplateParser {
       }
       Expr::Object(obj) => {
         if !self.context.is_parent_component() || self.context.root_container {
-          emit_error(obj.span(), "Slot 定义必须位于组件下");
+          self.emit_error_placeholder(obj.span(), DiagCode::SlotOutsideComponent);
           return;
         }
         obj.props.iter().for_each(|prop| match prop {
           PropOrSpread::Spread(e) => {
-            emit_error(e.dot3_token.span(), "Slot 定义不支持 ... 的书写方式");
+            emit_error(e.dot3_token.span(), DiagCode::SlotDefSpread);
           }
           PropOrSpread::Prop(p) => match p.as_ref() {
             Prop::KeyValue(KeyValueProp { key, value }) => {
@@ -276,17 +254,19 @@ impl Visit for TemplateParser {
                 PropName::Ident(id) => self.context.slots.push(Slot::new(id.sym.clone())),
                 PropName::Str(s) => self.context.slots.push(Slot::new(s.value.clone())),
                 _ => {
-                  emit_error(key.span(), "Slot 定义的名称必须是常量字符串");
+                  emit_error(key.span(), DiagCode::SlotNameNotConst);
                   return;
                 }
               }
               self.visit_expr(value);
             }
-            _ => emit_error(p.span(), "Slot 定义必须是 Key: Value 的形式"),
+            _ => emit_error(p.span(), DiagCode::SlotDefNotKeyValue),
           },
         })
       }
-      Expr::Array(e) => emit_error(e.span(), "tsx 中不能直接使用数组表达式"),
+      Expr::Array(e) => {
+        self.emit_error_placeholder(e.span(), DiagCode::ArrayInTemplate);
+      }
       Expr::Paren(e) => self.visit_expr(&e.expr),
       Expr::Lit(e) => self.visit_lit(e),
       Expr::Call(expr) => {
@@ -317,21 +297,13 @@ impl Visit for TemplateParser {
     let Some(text) = trim_html_text(text) else {
       return;
     };
-    self.push_expression(tpl_render_const_text(
-      ast_create_expr_lit_str(text),
-      self.context.is_parent_component(),
-      self.context.root_container,
-    ))
+    self.push_node(Node::Text(ast_create_expr_lit_str(text)))
   }
   fn visit_lit(&mut self, n: &Lit) {
     if let Lit::JSXText(t) = n {
       self.visit_jsx_text(t);
     } else {
-      self.push_expression(tpl_render_const_text(
-        Box::new(Expr::Lit(n.clone())),
-        self.context.is_parent_component(),
-        self.context.root_container,
-      ))
+      self.push_node(Node::Text(Box::new(Expr::Lit(n.clone()))))
     };
   }
 }
@@ -404,3 +376,101 @@ fn test_trim_html_text() {
   t = Atom::from("  a \n\n\n b c \n d ");
   assert_eq!(trim_html_text(&t), Some(Atom::from("  a b c d ")));
 }
+
+#[cfg(test)]
+mod tests {
+  use std::rc::Rc;
+  use std::sync::Arc;
+
+  use swc_common::input::SourceFileInput;
+  use swc_common::{FileName, SourceMap};
+  use swc_core::ecma::ast::EsVersion;
+  use swc_ecma_parser::{lexer::Lexer, Parser, Syntax, TsSyntax};
+
+  use super::ir::{Node, Value};
+  use super::TemplateParser;
+  use crate::common::IntlType;
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_error_recovery() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A() {
+  return <div>
+    <a:b />
+    {[1, 2]}
+    <span>{function () {}}</span>
+    <p>ok</p>
+  </div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .filter_map(|d| d.code.as_deref())
+      .collect();
+    assert_eq!(codes, ["JG2001", "JG2005", "JG2004"]);
+    assert!(code.contains("throw new Error(\"[JG2001]"));
+    assert!(code.contains("\"ok\""));
+
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A() { return <div>; }".into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    assert!(!diagnostics.is_empty());
+  }
+
+  #[test]
+  fn test_template_ir() {
+    let cm: Arc<SourceMap> = Arc::<SourceMap>::default();
+    let fm = cm.new_source_file(
+      Arc::new(FileName::Anon),
+      "<div class=\"a\" title={props.t}>{props.a ? <A /> : null}{props.list.map((x) => <p>{x}</p>)}</div>"
+        .into(),
+    );
+    let lexer = Lexer::new(
+      Syntax::Typescript(TsSyntax {
+        tsx: true,
+        ..Default::default()
+      }),
+      EsVersion::latest(),
+      SourceFileInput::from(&*fm),
+      None,
+    );
+    let expr = Parser::new_from(lexer).parse_expr().unwrap();
+    let nodes = TemplateParser::new(
+      Some("props".into()),
+      IntlType::Disabled,
+      Rc::default(),
+      Rc::default(),
+    )
+    .parse(&expr)
+    .unwrap();
+    let [Node::Element(div)] = nodes.as_slice() else {
+      panic!("expect single element");
+    };
+    assert_eq!(div.tag.sym, "div");
+    assert!(matches!(div.attrs[0].1, Value::Const(_)));
+    assert!(matches!(div.attrs[1].1, Value::Bind { .. }));
+    let [Node::If(cond), Node::For(looop)] = div.children.as_slice() else {
+      panic!("expect If and For");
+    };
+    assert_eq!(cond.slots[1].name, "true");
+    assert!(matches!(cond.slots[1].children[0], Node::Component(_)));
+    assert!(matches!(looop.slots[0].children[0], Node::Element(_)));
+  }
+}
@@ -0,0 +1,242 @@
+use hashbrown::HashMap;
+use swc_core::common::{Spanned, DUMMY_SP};
+use swc_core::ecma::ast::*;
+use swc_core::ecma::visit::{noop_visit_mut_type, VisitMut, VisitMutWith};
+
+use crate::ast::{ast_create_expr_ident, ast_create_expr_member};
+use crate::common::{
+  emit_error, emit_error_with_args, global_ident, DiagCode, JINGE_PROPS_IDENT, JINGE_UNDEFINED,
+};
+
+/// 将函数组件 props 参数的对象解构写法，例如 `function A({ title, size = 10, user: { name }, children })`，
+/// 改写为合成的 props 参数 `props$jg$`，函数体内对解构变量的引用替换为对应的成员表达式：
+/// `title` 替换为 `props$jg$.title`，有默认值的 `size` 替换为 `props$jg$.size === undefined ? 10 : props$jg$.size`。
+/// 和解构一样，默认值只在属性为 undefined 时才求值。
+///
+/// 替换后模板中的表达式仍然是 props 上的 member 表达式，因此 PathWatcher 监听和 `children` Slot 的识别都能正常工作，
+/// 带默认值的表达式参看 [as_prop_default]。解构变量是只读的，给它们赋值会报错。
+/// 注意 `...rest` 剩余属性直接替换为 `props$jg$`，即会包含已经解构出来的属性。
+pub fn rewrite_destructured_props<N: VisitMutWith<PropsReplaceVisitor>>(
+  pat: &mut Pat,
+  body: &mut N,
+) {
+  let Pat::Object(obj) = pat else {
+    return;
+  };
+  let props = JINGE_PROPS_IDENT.ident();
+  let mut binder = PropsBinder {
+    replaces: HashMap::new(),
+  };
+  binder.bind_object_pat(obj, ast_create_expr_ident(props.clone()));
+  *pat = Pat::Ident(BindingIdent::from(props));
+  if !binder.replaces.is_empty() {
+    body.visit_mut_with(&mut PropsReplaceVisitor {
+      replaces: binder.replaces,
+    });
+  }
+}
+
+struct PropsBinder {
+  replaces: HashMap<Id, Box<Expr>>,
+}
+impl PropsBinder {
+  fn bind_object_pat(&mut self, obj: &ObjectPat, base: Box<Expr>) {
+    obj.props.iter().for_each(|prop| match prop {
+      ObjectPatProp::KeyValue(kv) => {
+        let member = ast_create_expr_member(base.clone(), prop_name_to_member(&kv.key));
+        self.bind_pat(&kv.value, member);
+      }
+      ObjectPatProp::Assign(p) => {
+        let member = ast_create_expr_member(
+          base.clone(),
+          MemberProp::Ident(IdentName::from(p.key.sym.clone())),
+        );
+        let expr = match &p.value {
+          Some(default_value) => self.with_default(member, default_value.clone()),
+          None => member,
+        };
+        self.replaces.insert(p.key.to_id(), expr);
+      }
+      ObjectPatProp::Rest(r) => self.bind_pat(&r.arg, base.clone()),
+    });
+  }
+
+  fn bind_pat(&mut self, pat: &Pat, expr: Box<Expr>) {
+    match pat {
+      Pat::Ident(id) => {
+        self.replaces.insert(id.to_id(), expr);
+      }
+      Pat::Assign(p) => {
+        let expr = self.with_default(expr, p.right.clone());
+        self.bind_pat(&p.left, expr)
+      }
+      Pat::Object(obj) => self.bind_object_pat(obj, expr),
+      _ => emit_error(pat.span(), DiagCode::PropsDestructure),
+    }
+  }
+
+  /// 默认值中可以引用前面解构出来的属性，例如 `{ a, b = a }`，先替换为对应的成员表达式。
+  fn with_default(&mut self, expr: Box<Expr>, mut default_value: Box<Expr>) -> Box<Expr> {
+    if !self.replaces.is_empty() {
+      default_value.visit_mut_with(&mut PropsReplaceVisitor {
+        replaces: self.replaces.clone(),
+      });
+    }
+    with_default(expr, default_value)
+  }
+}
+
+fn prop_name_to_member(key: &PropName) -> MemberProp {
+  match key {
+    PropName::Ident(id) => MemberProp::Ident(id.clone()),
+    PropName::Str(s) => MemberProp::Computed(ComputedPropName {
+      span: DUMMY_SP,
+      expr: Box::new(Expr::Lit(Lit::Str(s.clone()))),
+    }),
+    PropName::Num(n) => MemberProp::Computed(ComputedPropName {
+      span: DUMMY_SP,
+      expr: Box::new(Expr::Lit(Lit::Num(n.clone()))),
+    }),
+    PropName::BigInt(n) => MemberProp::Computed(ComputedPropName {
+      span: DUMMY_SP,
+      expr: Box::new(Expr::Lit(Lit::BigInt(n.clone()))),
+    }),
+    PropName::Computed(c) => MemberProp::Computed(c.clone()),
+  }
+}
+
+/// 生成 `expr === undefined ? default_value : expr`，和解构默认值的语义保持一致（只有 undefined 才使用默认值）。
+pub fn with_default(expr: Box<Expr>, default_value: Box<Expr>) -> Box<Expr> {
+  Box::new(Expr::Cond(CondExpr {
+    span: DUMMY_SP,
+    test: Box::new(Expr::Bin(BinExpr {
+      span: DUMMY_SP,
+      op: BinaryOp::EqEqEq,
+      left: expr.clone(),
+      right: Box::new(Expr::Ident(global_ident(JINGE_UNDEFINED.clone()))),
+    })),
+    cons: default_value,
+    alt: expr,
+  }))
+}
+
+/// 识别 [with_default] 生成的表达式，返回其中的 member 表达式和默认值。
+/// 模板中监听这样的表达式时只需要监听 member 表达式一次，参看 [super::expr::ExprVisitor]。
+pub fn as_prop_default(expr: &Expr) -> Option<(&MemberExpr, &Expr)> {
+  let expr = match expr {
+    Expr::Paren(e) => return as_prop_default(e.expr.as_ref()),
+    Expr::Cond(e) => e,
+    _ => return None,
+  };
+  let (Expr::Bin(test), Expr::Member(alt)) = (expr.test.as_ref(), expr.alt.as_ref()) else {
+    return None;
+  };
+  match (test.left.as_ref(), test.right.as_ref()) {
+    (Expr::Member(left), Expr::Ident(undef))
+      if test.op == BinaryOp::EqEqEq && left == alt && JINGE_UNDEFINED.eq(&undef.sym) =>
+    {
+      Some((alt, expr.cons.as_ref()))
+    }
+    _ => None,
+  }
+}
+
+pub struct PropsReplaceVisitor {
+  replaces: HashMap<Id, Box<Expr>>,
+}
+impl VisitMut for PropsReplaceVisitor {
+  noop_visit_mut_type!();
+  fn visit_mut_expr(&mut self, node: &mut Expr) {
+    if let Expr::Ident(id) = node {
+      // 不需要包裹括号，最后的 fixer 会按需补充括号。
+      if let Some(expr) = self.replaces.get(&id.to_id()) {
+        *node = *expr.clone();
+      }
+    } else {
+      node.visit_mut_children_with(self);
+    }
+  }
+  fn visit_mut_binding_ident(&mut self, node: &mut BindingIdent) {
+    // 函数体内同名的声明会被 resolver 区分开，因此这里的 BindingIdent 只可能是赋值的目标。
+    if self.replaces.contains_key(&node.to_id()) {
+      emit_error_with_args(node.span, DiagCode::PropsAssign, &[&node.sym]);
+    }
+  }
+  fn visit_mut_update_expr(&mut self, node: &mut UpdateExpr) {
+    match node.arg.as_ref() {
+      Expr::Ident(id) if self.replaces.contains_key(&id.to_id()) => {
+        emit_error_with_args(id.span, DiagCode::PropsAssign, &[&id.sym]);
+      }
+      _ => node.visit_mut_children_with(self),
+    }
+  }
+  fn visit_mut_prop(&mut self, node: &mut Prop) {
+    if let Prop::Shorthand(id) = node {
+      // `{ title }` 需要展开为 `{ title: props$jg$.title }`
+      if let Some(expr) = self.replaces.get(&id.to_id()) {
+        *node = Prop::KeyValue(KeyValueProp {
+          key: PropName::Ident(IdentName::from(id.sym.clone())),
+          value: expr.clone(),
+        });
+      }
+    } else {
+      node.visit_mut_children_with(self);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_destructured_props() {
+    let TransformOutput {
+      code, diagnostics, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A({ title: t, size = 10, list = [], children }) {
+  return <div title={t}>{size * 2}{list.length}{children()}</div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::Zh),
+        ..Default::default()
+      },
+    );
+    assert!(diagnostics.is_empty());
+    assert!(code.contains("export function A(props$jg$)"));
+    assert!(code.contains("\"title\"\n    ], (v)=>setAttribute$jg$("));
+    // 和解构一样只在属性为 undefined 时才求值默认值，模板中只监听一次 props 上的属性
+    assert!(code.contains("(a0)=>(a0 === undefined ? 10 : a0) * 2)"));
+    assert!(code.contains("\"list\",\n            \"length\""));
+    assert!(code.contains("=>(props$jg$.list === undefined ? [] : props$jg$.list).length)"));
+    assert!(code.contains("renderSlotFunction$jg$"));
+
+    // 解构出来的 props 属性是只读的
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A({ count }) {
+  const inc = () => { count = count + 1; count++; };
+  return <div onClick={() => inc()}>{count}</div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let spans: Vec<_> = diagnostics
+      .iter()
+      .map(|d| (d.code.as_deref(), d.line, d.column))
+      .collect();
+    assert_eq!(spans, [(Some("JG1006"), 2, 23), (Some("JG1006"), 2, 42)]);
+    assert_eq!(
+      diagnostics[0].message,
+      "Cannot assign to `count`: destructured props are read-only"
+    );
+  }
+}
@@ -1,148 +1,542 @@
-use swc_common::Spanned;
+use std::cell::Cell;
+use std::rc::Rc;
+
+use swc_common::util::take::Take;
+use swc_common::{Span, Spanned, DUMMY_SP};
+use swc_core::atoms::Atom;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::VisitMut;
-use swc_ecma_visit::VisitMutWith;
+use swc_ecma_visit::{noop_visit_mut_type, noop_visit_type, Visit, VisitMutWith, VisitWith};
 
-use crate::ast::{ast_create_arg_expr, ast_create_expr_ident, ast_create_expr_lit_str};
-use crate::common::{emit_error, IntlType, JINGE_IMPORT_MODULE_ITEM, JINGE_T, JINGE_UNDEFINED};
+use crate::ast::{
+  ast_create_arg_expr, ast_create_expr_ident, ast_create_expr_lit_str, ast_create_stmt_decl_const,
+};
+use crate::codegen::{
+  gen_define_custom_element, gen_hoisted_templates_decl, DomCodegen, SsrCodegen,
+};
+use crate::common::{
+  collect_private_idents, emit_error, emit_error_with_args, gen_import_jinge, DiagCode, IntlType,
+  RenderMode, SourceLocator, JINGE_COMPONENT, JINGE_DEFAULT_COMPONENT, JINGE_DYNAMIC,
+  JINGE_EMPTY_STR, JINGE_RENDER, JINGE_T, JINGE_UNDEFINED,
+};
 use crate::parser;
+use crate::parser::binding::JingeBinding;
+use crate::parser::custom_element::{
+  is_valid_custom_element_name, CustomElementPragma, CustomElementPragmas, PropsUsage,
+  PropsUsageVisitor,
+};
 use crate::parser::intl::extract_t;
+use crate::parser::is_null_undef;
+use crate::parser::locals::{inline_jsx_locals, jsx_local_ids};
+use crate::parser::props::rewrite_destructured_props;
 
 pub struct TemplateTransformVisitor<'a> {
   changed: bool,
   pub parsed_components: &'a mut Vec<String>,
   pub intl_type: IntlType,
+  intl_t: Rc<JingeBinding>,
+  /// 从 jinge 导入的 Dynamic 组件，参看 [crate::parser::ir::Dynamic]
+  dynamic: Rc<JingeBinding>,
+  /// 从 jinge 导入的 Component 基类，类组件必须继承它，参看 v_class
+  component: JingeBinding,
+  /// 高阶函数组件的包装函数白名单，例如 `["withTheme", "React.memo"]`，参看 v_init
+  hoc_wrappers: &'a [String],
+  /// 提升到模块顶部的静态模板数量，参看 codegen/hoist.rs
+  hoisted_templates: Rc<Cell<usize>>,
+  /// 模板的编译目标，参看 [RenderMode]
+  render_mode: RenderMode,
+  /// 开发模式下 hydrate 代码标注源码位置使用，参看 [DomCodegen::hydrate]
+  locator: Option<SourceLocator>,
+  /// 源码中的 `jinge-custom-element` 注释，参看 [CustomElementPragma]
+  custom_element_pragmas: CustomElementPragmas,
+  /// 当前模块语句前的 `jinge-custom-element` 注释，该语句声明的函数组件编译后被取走
+  custom_element: Option<CustomElementPragma>,
+  /// 注册 Custom Element 的语句，放在模块末尾。SSR 不需要注册，因此总是为空。
+  custom_elements: Vec<ModuleItem>,
+  /// 只检查不生成代码，参看 [Self::check_only]
+  check_only: bool,
 }
 impl<'a> TemplateTransformVisitor<'a> {
-  pub fn new(parsed_components: &'a mut Vec<String>, intl_type: IntlType) -> Self {
+  pub fn new(
+    parsed_components: &'a mut Vec<String>,
+    intl_type: IntlType,
+    hoc_wrappers: &'a [String],
+    render_mode: RenderMode,
+    locator: Option<SourceLocator>,
+    custom_element_pragmas: CustomElementPragmas,
+  ) -> Self {
     Self {
       parsed_components,
       intl_type,
+      hoc_wrappers,
+      render_mode,
+      locator,
+      custom_element_pragmas,
+      custom_element: None,
+      custom_elements: vec![],
+      intl_t: Default::default(),
+      dynamic: Default::default(),
+      component: Default::default(),
+      hoisted_templates: Default::default(),
       changed: false,
+      check_only: false,
     }
   }
+  /// 只解析模板到 IR 并报告诊断信息，不生成渲染代码，也不插入 import 和 Custom Element 注册语句。
+  /// 组件 return 的 jsx 被替换为空渲染，转换后的 module 只用于后续的诊断检查，参看 lib.rs 的 inner_analyze。
+  pub fn check_only(mut self, check_only: bool) -> Self {
+    self.check_only = check_only;
+    self
+  }
+  fn v_var_declarator(&mut self, decl: &mut VarDeclarator) {
+    let Some(init) = decl.init.as_mut() else {
+      return;
+    };
+    let name = match &decl.name {
+      Pat::Ident(id) => Some(&id.id),
+      _ => {
+        emit_error(decl.name.span(), DiagCode::ComponentNameHmr);
+        None
+      }
+    };
+    self.v_init(name, init.as_mut());
+  }
+  /// 变量初始值或默认导出的表达式，可能是函数组件，也可能是被白名单中的高阶函数包裹的函数组件，
+  /// 例如 `withTheme((props) => <div />)`。被包裹的组件以变量名注册 HMR。
+  fn v_init(&mut self, name: Option<&Ident>, init: &mut Expr) {
+    match init {
+      Expr::Fn(func) => self.v_func(name, func.function.as_mut()),
+      Expr::Arrow(func) => self.v_arrow(name, func),
+      Expr::Paren(e) => self.v_init(name, e.expr.as_mut()),
+      Expr::Class(c) => self.v_class(name, c.class.as_mut()),
+      Expr::Call(call) if self.is_hoc_wrapper(&call.callee) => {
+        let count = self.parsed_components.len();
+        for arg in call.args.iter_mut().filter(|arg| arg.spread.is_none()) {
+          self.v_init(name, arg.expr.as_mut());
+          // 只有第一个函数参数作为组件。
+          if self.parsed_components.len() > count {
+            break;
+          }
+        }
+      }
+      _ => (),
+    }
+  }
+  /// 继承 jinge `Component` 的类组件，编译 `render()` 方法返回的 jsx，并以类名注册 HMR。
+  /// 类组件中模板的表达式以 `this` 为根，参看 ExprVisitor 的 `Root::This`。
+  /// 父类通过 import 绑定判定，`React.Component` 或本地定义的同名类不会被当作 jinge 类组件。
+  fn v_class(&mut self, name: Option<&Ident>, class: &mut Class) {
+    // 类组件的 props 无法静态分析，不支持注册为 Custom Element。
+    if let Some(pragma) = self.custom_element.take() {
+      emit_error(pragma.span, DiagCode::CustomElementTarget);
+    }
+    if !class
+      .super_class
+      .as_deref()
+      .is_some_and(|sc| self.component.is(sc))
+    {
+      return;
+    }
+    class.body.iter_mut().for_each(|member| {
+      let ClassMember::Method(method) = member else {
+        return;
+      };
+      if method.is_static
+        || method.kind != MethodKind::Method
+        || !matches!(&method.key, PropName::Ident(k) if JINGE_RENDER.eq(&k.sym))
+      {
+        return;
+      }
+      self.v_func(name, method.function.as_mut());
+    });
+  }
+  fn is_hoc_wrapper(&self, callee: &Callee) -> bool {
+    let Callee::Expr(callee) = callee else {
+      return false;
+    };
+    callee_name(callee.as_ref()).is_some_and(|n| self.hoc_wrappers.iter().any(|w| w.eq(&n)))
+  }
   fn v_func(&mut self, fn_name: Option<&Ident>, expr: &mut Function) {
     if let Some(body) = &mut expr.body {
-      self.v_func_body(fn_name, body, expr.params.get(0).map(|p| &p.pat));
+      self.v_func_body(fn_name, body, expr.params.get_mut(0).map(|p| &mut p.pat));
     };
   }
-  fn v_func_body(&mut self, fn_name: Option<&Ident>, body: &mut BlockStmt, prop_arg: Option<&Pat>) {
-    let Some(Stmt::Return(stmt)) = body.stmts.last_mut() else {
-      return;
+  fn v_func_body(
+    &mut self,
+    fn_name: Option<&Ident>,
+    body: &mut BlockStmt,
+    mut props_arg: Option<&mut Pat>,
+  ) {
+    // 函数体内任意一个 return 返回了 jsx，就认为是组件，需要转换所有 return 语句（包括 if/switch 等语句块中的提前 return）。
+    let mut finder = JsxReturnFinder {
+      found: false,
+      jsx_locals: jsx_local_ids(body),
     };
-    let Some(expr) = &mut stmt.arg else {
+    body.visit_with(&mut finder);
+    if !finder.found {
       return;
+    }
+    inline_jsx_locals(body);
+    if let Some(pat) = props_arg.as_deref_mut() {
+      rewrite_destructured_props(pat, body);
+    }
+    let props_arg = self.props_arg_name(props_arg.as_deref());
+    let usage = self.custom_element_usage(props_arg.as_ref(), body);
+    let mut v = ReturnVisitor {
+      tv: self,
+      props_arg,
+      parsed: false,
     };
-    if is_jsx(expr.as_ref()) {
-      self.v_return(fn_name, expr, prop_arg);
+    body.visit_mut_with(&mut v);
+    if v.parsed {
+      self.on_component_parsed(fn_name, usage);
     }
   }
   fn v_arrow(&mut self, fn_name: Option<&Ident>, expr: &mut ArrowExpr) {
     match expr.body.as_mut() {
       BlockStmtOrExpr::Expr(e) => {
         if is_jsx(e.as_ref()) {
-          self.v_return(fn_name, e, expr.params.get(0));
+          if let Some(pat) = expr.params.get_mut(0) {
+            rewrite_destructured_props(pat, e);
+          }
+          let props_arg = self.props_arg_name(expr.params.first());
+          let usage = self.custom_element_usage(props_arg.as_ref(), e);
+          if let Some(mut stmts) = self.v_return(e, props_arg) {
+            if !stmts.is_empty() {
+              stmts.push(Stmt::Return(ReturnStmt {
+                span: DUMMY_SP,
+                arg: Some(e.clone()),
+              }));
+              *expr.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                span: DUMMY_SP,
+                ctxt: Default::default(),
+                stmts,
+              });
+            }
+            self.on_component_parsed(fn_name, usage);
+          }
         }
       }
-      BlockStmtOrExpr::BlockStmt(body) => self.v_func_body(fn_name, body, expr.params.get(0)),
+      BlockStmtOrExpr::BlockStmt(body) => self.v_func_body(fn_name, body, expr.params.get_mut(0)),
     }
   }
 
-  fn v_return(&mut self, fn_name: Option<&Ident>, expr: &mut Box<Expr>, props_arg: Option<&Pat>) {
-    let mut visitor = parser::TemplateParser::new(
-      props_arg.and_then(|p| {
-        if let Pat::Ident(id) = p {
-          Some(id.sym.clone())
-        } else {
-          emit_error(props_arg.span(), "函数组件的 props 参数不能使用解构写法");
-          None
+  fn props_arg_name(&self, props_arg: Option<&Pat>) -> Option<Atom> {
+    props_arg.and_then(|p| {
+      if let Pat::Ident(id) = p {
+        Some(id.sym.clone())
+      } else {
+        emit_error(props_arg.span(), DiagCode::PropsDestructure);
+        None
+      }
+    })
+  }
+  /// 当前语句有 `jinge-custom-element` 注释时，在模板编译之前分析组件用到的 props。
+  fn custom_element_usage<N: VisitWith<PropsUsageVisitor>>(
+    &self,
+    props_arg: Option<&Atom>,
+    node: &N,
+  ) -> Option<PropsUsage> {
+    self.custom_element.as_ref()?;
+    Some(
+      props_arg
+        .map(|p| PropsUsage::collect(p.clone(), node))
+        .unwrap_or_default(),
+    )
+  }
+  fn on_component_parsed(&mut self, fn_name: Option<&Ident>, usage: Option<PropsUsage>) {
+    self.changed = true;
+    let Some(fn_name) = fn_name else {
+      return;
+    };
+    self.parsed_components.push(fn_name.sym.to_string());
+    if let Some(usage) = usage {
+      let pragma = self.custom_element.take().unwrap();
+      if self.render_mode != RenderMode::Ssr && !self.check_only {
+        self.custom_elements.push(gen_define_custom_element(
+          pragma.tag,
+          fn_name.clone(),
+          usage,
+        ));
+      }
+    }
+  }
+  /// 取出 item 之前的 `jinge-custom-element` 注释，名称不合法时报错并忽略。
+  fn take_custom_element_pragma(&mut self, item: &ModuleItem) {
+    self.custom_element = self
+      .custom_element_pragmas
+      .take(item.span_lo())
+      .filter(|pragma| {
+        let valid = is_valid_custom_element_name(&pragma.tag);
+        if !valid {
+          emit_error_with_args(pragma.span, DiagCode::CustomElementName, &[&pragma.tag]);
         }
-      }),
+        valid
+      });
+  }
+
+  /// 将 return 的 jsx 表达式替换为渲染出的节点数组（SSR 时是 html 字符串），并返回需要在 return 之前执行的渲染语句。
+  /// 返回 None 表示没有转换。
+  fn v_return(&mut self, expr: &mut Box<Expr>, props_arg: Option<Atom>) -> Option<Vec<Stmt>> {
+    let mut visitor = parser::TemplateParser::new(
+      props_arg,
       self.intl_type,
+      self.intl_t.clone(),
+      self.dynamic.clone(),
     );
-    if let Some(replaced_expr) = visitor.parse(expr.as_mut()) {
-      *expr = replaced_expr;
-      self.changed = true;
-      if let Some(fn_name) = fn_name {
-        self.parsed_components.push(fn_name.sym.to_string());
+    let nodes = visitor.parse(expr.as_mut())?;
+    if self.check_only {
+      *expr = self.empty_render();
+      return Some(vec![]);
+    }
+    let (stmts, replaced_expr) = match self.render_mode {
+      RenderMode::Dom => DomCodegen::new(self.hoisted_templates.clone()).gen(nodes),
+      RenderMode::Ssr => (vec![], SsrCodegen::default().gen(nodes)),
+      RenderMode::Hydrate => DomCodegen::new(self.hoisted_templates.clone())
+        .hydrate(self.locator.clone())
+        .gen(nodes),
+    };
+    *expr = replaced_expr;
+    Some(stmts)
+  }
+  /// 不渲染任何内容的返回值：DOM 渲染是空的节点数组，SSR 是空字符串。
+  fn empty_render(&self) -> Box<Expr> {
+    match self.render_mode {
+      RenderMode::Dom | RenderMode::Hydrate => Box::new(Expr::Array(ArrayLit {
+        span: DUMMY_SP,
+        elems: vec![],
+      })),
+      RenderMode::Ssr => ast_create_expr_lit_str(JINGE_EMPTY_STR.clone()),
+    }
+  }
+}
+
+/// 查找函数体内是否有返回 jsx 的 return 语句，不进入嵌套的函数和类。
+struct JsxReturnFinder {
+  found: bool,
+  /// 函数体顶层绑定了 jsx 的局部变量，返回这些变量也认为是返回了 jsx，参看 [jsx_local_ids]
+  jsx_locals: Vec<Id>,
+}
+impl JsxReturnFinder {
+  fn is_jsx_or_local(&self, expr: &Expr) -> bool {
+    match expr {
+      Expr::Ident(id) => self.jsx_locals.contains(&id.to_id()),
+      Expr::Paren(e) => self.is_jsx_or_local(e.expr.as_ref()),
+      Expr::Cond(e) => {
+        self.is_jsx_or_local(e.alt.as_ref()) || self.is_jsx_or_local(e.cons.as_ref())
       }
+      Expr::Bin(e) => self.is_jsx_or_local(e.right.as_ref()),
+      _ => is_jsx(expr),
     }
   }
 }
+impl Visit for JsxReturnFinder {
+  noop_visit_type!();
+  fn visit_return_stmt(&mut self, n: &ReturnStmt) {
+    if n
+      .arg
+      .as_ref()
+      .is_some_and(|arg| self.is_jsx_or_local(arg.as_ref()))
+    {
+      self.found = true;
+    }
+  }
+  fn visit_function(&mut self, _: &Function) {}
+  fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+  fn visit_class(&mut self, _: &Class) {}
+}
+
+/// 转换组件函数体内的所有 return 语句。返回 null/undefined 的 return 转换为不渲染任何内容的空数组（SSR 时是空字符串）。
+struct ReturnVisitor<'a, 'b> {
+  tv: &'a mut TemplateTransformVisitor<'b>,
+  props_arg: Option<Atom>,
+  parsed: bool,
+}
+impl ReturnVisitor<'_, '_> {
+  /// 转换 return 语句，返回需要在 return 之前执行的渲染语句。
+  fn v_return_stmt(&mut self, ret: &mut ReturnStmt) -> Vec<Stmt> {
+    match &mut ret.arg {
+      Some(arg) if is_jsx(arg.as_ref()) => {
+        let Some(stmts) = self.tv.v_return(arg, self.props_arg.clone()) else {
+          return vec![];
+        };
+        self.parsed = true;
+        stmts
+      }
+      Some(arg) if !is_null_undef(arg.as_ref()) => vec![],
+      _ => {
+        ret.arg = Some(self.tv.empty_render());
+        vec![]
+      }
+    }
+  }
+}
+impl VisitMut for ReturnVisitor<'_, '_> {
+  noop_visit_mut_type!();
+  fn visit_mut_stmts(&mut self, n: &mut Vec<Stmt>) {
+    // 语句列表中最后的 return 语句，渲染语句直接放在 return 之前。
+    let last_ret = match n.last() {
+      Some(Stmt::Return(_)) => n.pop(),
+      _ => None,
+    };
+    n.iter_mut().for_each(|stmt| stmt.visit_mut_with(self));
+    if let Some(Stmt::Return(mut ret)) = last_ret {
+      let stmts = self.v_return_stmt(&mut ret);
+      n.extend(stmts);
+      n.push(Stmt::Return(ret));
+    }
+  }
+  fn visit_mut_stmt(&mut self, n: &mut Stmt) {
+    let Stmt::Return(ret) = n else {
+      n.visit_mut_children_with(self);
+      return;
+    };
+    let mut stmts = self.v_return_stmt(ret);
+    if !stmts.is_empty() {
+      // 渲染语句和 return 一起放到语句块中：`if (x) { const $jg$0 = ...; return [$jg$0]; }`
+      stmts.push(n.take());
+      *n = Stmt::Block(BlockStmt {
+        span: DUMMY_SP,
+        ctxt: Default::default(),
+        stmts,
+      });
+    }
+  }
+  fn visit_mut_switch_case(&mut self, n: &mut SwitchCase) {
+    // 所有 case 共享 switch 的作用域，每个 return 的渲染语句都要放到各自的语句块中，否则局部变量会重复声明。
+    n.test.visit_mut_with(self);
+    n.cons.iter_mut().for_each(|stmt| stmt.visit_mut_with(self));
+  }
+  fn visit_mut_function(&mut self, _: &mut Function) {}
+  fn visit_mut_arrow_expr(&mut self, _: &mut ArrowExpr) {}
+  fn visit_mut_class(&mut self, _: &mut Class) {}
+}
+
 impl VisitMut for TemplateTransformVisitor<'_> {
   fn visit_mut_module(&mut self, n: &mut Module) {
-    n.body.iter_mut().for_each(|item| match item {
-      ModuleItem::ModuleDecl(decl) => match decl {
-        ModuleDecl::ExportDecl(decl) => match &mut decl.decl {
-          Decl::Fn(func) => self.v_func(Some(&func.ident), func.function.as_mut()),
-
-          Decl::Var(decl) => decl.as_mut().decls.iter_mut().for_each(|decl| {
-            if let Some(x) = &mut decl.init {
-              let name = match &decl.name {
-                Pat::Ident(id) => Some(&id.id),
-                _ => {
-                  emit_error(decl.name.span(), "警告：非常规命令的函数组件无法使用 HMR");
-                  None
+    // 绑定总是收集，是否把 t 函数编译为国际化文本由 TemplateParser 根据 intl_type 决定。
+    self.intl_t = Rc::new(JingeBinding::collect(n, &JINGE_T));
+    self.dynamic = Rc::new(JingeBinding::collect(n, &JINGE_DYNAMIC));
+    self.component = JingeBinding::collect(n, &JINGE_COMPONENT);
+    // `export default () => <div />` 形式的组件，需要拆分成具名的声明和默认导出，参看 split_default_expr
+    let mut default_expr_idx = None;
+    n.body.iter_mut().enumerate().for_each(|(idx, item)| {
+      self.take_custom_element_pragma(item);
+      match item {
+        ModuleItem::ModuleDecl(decl) => match decl {
+          ModuleDecl::ExportDecl(decl) => match &mut decl.decl {
+            Decl::Fn(func) => self.v_func(Some(&func.ident), func.function.as_mut()),
+            Decl::Class(c) => self.v_class(Some(&c.ident), c.class.as_mut()),
+
+            Decl::Var(decl) => decl
+              .as_mut()
+              .decls
+              .iter_mut()
+              .for_each(|decl| self.v_var_declarator(decl)),
+            _ => (),
+          },
+          ModuleDecl::ExportDefaultDecl(decl) => match &mut decl.decl {
+            DefaultDecl::Fn(func) => {
+              if let Some(name) = &func.ident {
+                self.v_func(Some(name), func.function.as_mut());
+              } else {
+                // 匿名的默认导出函数，编译成功后补充合成的函数名，这样才能注册 HMR。
+                let name = Ident::from(JINGE_DEFAULT_COMPONENT.clone());
+                let count = self.parsed_components.len();
+                self.v_func(Some(&name), func.function.as_mut());
+                if self.parsed_components.len() > count {
+                  func.ident = Some(name);
                 }
-              };
-              match x.as_mut() {
-                Expr::Fn(func) => self.v_func(name, func.function.as_mut()),
-                Expr::Arrow(func) => self.v_arrow(name, func),
-                _ => (),
               }
             }
-          }),
-          _ => (),
-        },
-        ModuleDecl::ExportDefaultDecl(decl) => match &mut decl.decl {
-          DefaultDecl::Fn(func) => self.v_func(
-            if let Some(n) = &func.ident {
-              Some(n)
-            } else {
-              emit_error(func.span(), "警告：匿名函数组件无法使用 HMR");
-              None
-            },
-            func.function.as_mut(),
-          ),
-          _ => (),
-        },
-        _ => (),
-      },
-      ModuleItem::Stmt(stmt) => match stmt {
-        Stmt::Decl(decl) => match decl {
-          Decl::Fn(decl) => self.v_func(Some(&decl.ident), decl.function.as_mut()),
-          Decl::Var(decl) => decl.decls.iter_mut().for_each(|decl| {
-            if let Some(init) = decl.init.as_mut() {
-              let name = match &decl.name {
-                Pat::Ident(id) => Some(&id.id),
-                _ => {
-                  emit_error(decl.name.span(), "警告：非常规命令的函数组件无法使用 HMR");
-                  None
+            DefaultDecl::Class(c) => {
+              if let Some(name) = &c.ident {
+                self.v_class(Some(name), c.class.as_mut());
+              } else {
+                let name = Ident::from(JINGE_DEFAULT_COMPONENT.clone());
+                let count = self.parsed_components.len();
+                self.v_class(Some(&name), c.class.as_mut());
+                if self.parsed_components.len() > count {
+                  c.ident = Some(name);
                 }
-              };
-              match init.as_mut() {
-                Expr::Fn(func) => self.v_func(name, func.function.as_mut()),
-                Expr::Arrow(func) => self.v_arrow(name, func),
-                _ => (),
               }
             }
-          }),
+            _ => (),
+          },
+          ModuleDecl::ExportDefaultExpr(decl) => {
+            let name = Ident::from(JINGE_DEFAULT_COMPONENT.clone());
+            let count = self.parsed_components.len();
+            self.v_init(Some(&name), decl.expr.as_mut());
+            if self.parsed_components.len() > count {
+              default_expr_idx = Some(idx);
+            }
+          }
+          _ => (),
+        },
+        ModuleItem::Stmt(Stmt::Decl(decl)) => match decl {
+          Decl::Fn(decl) => self.v_func(Some(&decl.ident), decl.function.as_mut()),
+          Decl::Class(c) => self.v_class(Some(&c.ident), c.class.as_mut()),
+          Decl::Var(decl) => decl
+            .decls
+            .iter_mut()
+            .for_each(|decl| self.v_var_declarator(decl)),
           _ => (),
         },
         _ => (),
-      },
+      }
+      if let Some(pragma) = self.custom_element.take() {
+        emit_error(pragma.span, DiagCode::CustomElementTarget);
+      }
     });
 
-    if self.changed {
-      let mut new_items = Vec::with_capacity(n.body.len() + 1);
-      new_items.push(JINGE_IMPORT_MODULE_ITEM.clone());
+    if let Some(idx) = default_expr_idx {
+      split_default_expr(&mut n.body, idx);
+    }
+
+    if self.changed && !self.check_only {
+      let mut new_items = Vec::with_capacity(n.body.len() + 2 + self.custom_elements.len());
+      let mut used = collect_private_idents(&n.body);
+      used.extend(collect_private_idents(&self.custom_elements));
+      new_items.extend(gen_import_jinge(&used));
+      if self.hoisted_templates.get() > 0 {
+        new_items.push(gen_hoisted_templates_decl(self.hoisted_templates.get()));
+      }
       new_items.append(&mut n.body);
+      new_items.append(&mut self.custom_elements);
 
       n.body = new_items;
     }
   }
 }
 
+/// 高阶函数的名称，支持 `withTheme` 和 `React.memo` 这样的成员表达式。
+fn callee_name(expr: &Expr) -> Option<String> {
+  match expr {
+    Expr::Ident(id) => Some(id.sym.to_string()),
+    Expr::Member(m) => {
+      let MemberProp::Ident(prop) = &m.prop else {
+        return None;
+      };
+      callee_name(m.obj.as_ref()).map(|obj| format!("{}.{}", obj, prop.sym))
+    }
+    _ => None,
+  }
+}
+
+/// 把 `export default <expr>` 拆分为 `const default$jg$ = <expr>; export default default$jg$;`，
+/// 使组件在模块作用域内有名称，从而可以注册 HMR。
+fn split_default_expr(body: &mut Vec<ModuleItem>, idx: usize) {
+  let ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(decl)) = &mut body[idx] else {
+    return;
+  };
+  let name = Ident::from(JINGE_DEFAULT_COMPONENT.clone());
+  let expr = std::mem::replace(&mut decl.expr, ast_create_expr_ident(name.clone()));
+  body.insert(
+    idx,
+    ModuleItem::Stmt(ast_create_stmt_decl_const(name, expr)),
+  );
+}
+
 fn is_jsx(expr: &Expr) -> bool {
   match expr {
     Expr::Paren(expr) => is_jsx(expr.expr.as_ref()),
@@ -153,21 +547,109 @@ fn is_jsx(expr: &Expr) -> bool {
   }
 }
 
+#[derive(Clone, Copy)]
+enum FnKind {
+  Function,
+  Callback,
+  Method,
+}
+
+/// 模板转换之后仍然残留的 jsx 不会被编译，运行时才会出错。
+/// 找出这些 jsx，并根据所在的函数推断未被编译的原因，输出诊断信息。
+#[derive(Default)]
+pub struct LeftoverJsxVisitor {
+  fn_stack: Vec<FnKind>,
+  /// 即将访问的函数的类型，由父节点（类方法、函数调用的参数）设置。
+  pending: Option<FnKind>,
+}
+impl LeftoverJsxVisitor {
+  fn report(&self, sp: Span) {
+    let code = match self.fn_stack.last() {
+      Some(FnKind::Method) => DiagCode::JsxInClassMethod,
+      Some(FnKind::Callback) => DiagCode::JsxInCallback,
+      Some(FnKind::Function) if self.fn_stack.len() > 1 => DiagCode::JsxInNestedFunction,
+      _ => DiagCode::JsxNotCompiled,
+    };
+    emit_error(sp, code);
+  }
+  fn visit_fn_like(&mut self, f: impl FnOnce(&mut Self)) {
+    let kind = self.pending.take().unwrap_or(FnKind::Function);
+    self.fn_stack.push(kind);
+    f(self);
+    self.fn_stack.pop();
+  }
+  fn visit_args(&mut self, args: &[ExprOrSpread]) {
+    args.iter().for_each(|arg| {
+      if matches!(arg.expr.as_ref(), Expr::Fn(_) | Expr::Arrow(_)) {
+        self.pending = Some(FnKind::Callback);
+      }
+      arg.visit_with(self);
+    });
+  }
+}
+impl Visit for LeftoverJsxVisitor {
+  noop_visit_type!();
+  // 只报告最外层的 jsx，不再访问其子节点。
+  fn visit_jsx_element(&mut self, n: &JSXElement) {
+    self.report(n.span);
+  }
+  fn visit_jsx_fragment(&mut self, n: &JSXFragment) {
+    self.report(n.span);
+  }
+  fn visit_function(&mut self, n: &Function) {
+    self.visit_fn_like(|v| n.visit_children_with(v));
+  }
+  fn visit_arrow_expr(&mut self, n: &ArrowExpr) {
+    self.visit_fn_like(|v| n.visit_children_with(v));
+  }
+  fn visit_class_method(&mut self, n: &ClassMethod) {
+    n.key.visit_with(self);
+    self.pending = Some(FnKind::Method);
+    n.function.visit_with(self);
+  }
+  fn visit_private_method(&mut self, n: &PrivateMethod) {
+    self.pending = Some(FnKind::Method);
+    n.function.visit_with(self);
+  }
+  fn visit_constructor(&mut self, n: &Constructor) {
+    self.pending = Some(FnKind::Method);
+    self.visit_fn_like(|v| n.visit_children_with(v));
+  }
+  fn visit_call_expr(&mut self, n: &CallExpr) {
+    n.callee.visit_with(self);
+    self.visit_args(&n.args);
+  }
+  fn visit_new_expr(&mut self, n: &NewExpr) {
+    n.callee.visit_with(self);
+    if let Some(args) = &n.args {
+      self.visit_args(args);
+    }
+  }
+}
+
 pub struct IntlTransformVisitor {
   drop_default_text: bool,
+  intl_t: JingeBinding,
 }
 impl IntlTransformVisitor {
   pub fn new(drop_default_text: bool) -> Self {
-    IntlTransformVisitor { drop_default_text }
+    IntlTransformVisitor {
+      drop_default_text,
+      intl_t: Default::default(),
+    }
   }
 }
 impl VisitMut for IntlTransformVisitor {
+  fn visit_mut_module(&mut self, n: &mut Module) {
+    self.intl_t = JingeBinding::collect(n, &JINGE_T);
+    n.visit_mut_children_with(self);
+  }
   fn visit_mut_call_expr(&mut self, node: &mut CallExpr) {
     let Callee::Expr(callee) = &node.callee else {
       node.visit_mut_children_with(self);
       return;
     };
-    if !matches!(callee.as_ref(), Expr::Ident(name) if JINGE_T.eq(&name.sym)) {
+    if !self.intl_t.is(callee.as_ref()) {
       node.visit_mut_children_with(self);
       return;
     }
@@ -198,3 +680,162 @@ impl VisitMut for IntlTransformVisitor {
     node.args = args;
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::diagnostic::{DiagnosticPolicy, Lang};
+  use crate::{inner_transform, TransformOptions, TransformOutput};
+
+  #[test]
+  fn test_export_default_component() {
+    let TransformOutput {
+      code,
+      parsed_components,
+      ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export default (props) => <div>{props.a}</div>;".into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::Zh),
+        ..Default::default()
+      },
+    );
+    assert_eq!(parsed_components, "default$jg$");
+    assert!(code.contains("const default$jg$ = (props)=>{"));
+    assert!(code.contains("export default default$jg$;"));
+  }
+
+  #[test]
+  fn test_early_return() {
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  if (!props.user) return <p>login</p>;
+  if (props.hidden) return null;
+  return <div>{props.user}</div>;
+}"
+      .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::Zh),
+        ..Default::default()
+      },
+    );
+    assert!(!code.contains("<p>"));
+    assert!(code.contains("createEle$jg$(\"p\", \"login\")"));
+    assert!(code.contains("if (props.hidden) return [];"));
+  }
+
+  #[test]
+  fn test_switch_case_return() {
+    let TransformOutput { code, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export function A(props) {
+  switch (props.k) {
+    case 1: return <a>{props.x}</a>;
+    case 2: return <b>{props.y}</b>;
+  }
+}"
+      .into(),
+      TransformOptions::default(),
+    );
+    // 每个 case 的渲染语句放在各自的语句块中，局部变量不会在 switch 的作用域中重复声明
+    assert!(code.contains("case 1:\n            {\n                const "));
+    assert!(code.contains("case 2:\n            {\n                const "));
+  }
+
+  #[test]
+  fn test_hoc_component() {
+    let TransformOutput {
+      code,
+      parsed_components,
+      ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "export const Card = withTheme((props) => <div>{props.a}</div>);
+const List = React.memo(function (props) { return <ul />; });
+const Other = unknown((props) => <p />);"
+        .into(),
+      TransformOptions {
+        hoc_wrappers: vec!["withTheme".into(), "React.memo".into()],
+        ..Default::default()
+      },
+    );
+    assert_eq!(parsed_components, "Card,List");
+    assert!(code.contains("unknown((props)=><p/>)"));
+  }
+
+  #[test]
+  fn test_leftover_jsx() {
+    let TransformOutput { diagnostics, .. } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "const top = <div />;
+export function A(props) {
+  function inner() { return <b />; }
+  return <div>{props.list.map((x) => <li>{x}</li>)}</div>;
+}
+setTimeout(() => render(<p />));
+class X { render() { return <i />; } }"
+        .into(),
+      TransformOptions {
+        diagnostic: DiagnosticPolicy::new(Lang::En),
+        ..Default::default()
+      },
+    );
+    let codes: Vec<_> = diagnostics
+      .iter()
+      .filter_map(|d| d.code.as_deref())
+      .collect();
+    assert_eq!(codes, ["JG2011", "JG2010", "JG2009", "JG2008"]);
+  }
+
+  #[test]
+  fn test_class_component() {
+    let TransformOutput {
+      code,
+      parsed_components,
+      diagnostics,
+      ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "import { Component } from 'jinge';
+import * as jg from 'jinge';
+export class Card extends Component {
+  render() { return <div>{this.state.n}</div>; }
+}
+export class Box extends jg.Component {
+  render() { return <p>{this.state.n}</p>; }
+}
+class Plain { render() { return 1; } }"
+        .into(),
+      TransformOptions::default(),
+    );
+    assert!(diagnostics.is_empty());
+    assert!(code.contains("watchPathForRender$jg$(this"));
+    assert_eq!(parsed_components, "Card,Box");
+
+    // 不是从 jinge 导入的 Component 不会被编译
+    let TransformOutput {
+      parsed_components, ..
+    } = inner_transform(
+      "test.tsx".into(),
+      2,
+      "import React from 'react';
+class Component {}
+export class A extends Component {
+  render() { return <div />; }
+}
+export class B extends React.Component {
+  render() { return <div />; }
+}"
+      .into(),
+      TransformOptions::default(),
+    );
+    assert!(parsed_components.is_empty());
+  }
+}
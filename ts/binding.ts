@@ -3,6 +3,50 @@ import os from 'node:os';
 export interface TransformOptions {
   sourcemap?: boolean;
 }
+/** 编译诊断信息的语言 */
+export type DiagnosticLang = 'zh' | 'en';
+export interface BindingTransformOptions {
+  /**
+   * 诊断信息的语言，未指定时读取环境变量 `JINGE_COMPILER_LANG`，默认为 `zh`。
+   */
+  lang?: DiagnosticLang;
+  /** 将全部 warning 提升为 error，通常用于 CI。 */
+  warningsAsErrors?: boolean;
+  /** 存在 error 级别的诊断时抛出异常，而不是继续输出编译结果。 */
+  failOnError?: boolean;
+  /** 按诊断编号调整严重级别，例如 `{ JG1002: 'off', JG2103: 'error' }`。 */
+  rules?: Record<string, DiagnosticSeverity | 'off'>;
+  /**
+   * 高阶函数组件的包装函数白名单，例如 `['withTheme', 'React.memo']`。
+   * 模块顶层形如 `const Card = withTheme((props) => <div />)` 的组件会被编译，并以变量名注册 HMR。
+   */
+  hocWrappers?: string[];
+  /**
+   * 模板的编译目标，默认为 `dom`。
+   * `ssr` 用于服务端渲染：组件编译为返回转义后的 html 字符串的函数，只渲染一次，不监听数据变更，也不绑定事件。
+   * `hydrate` 用于接管服务端渲染出的页面：按文档顺序查找已有的 DOM 节点而不是重新创建，然后监听数据变更、绑定事件。
+   */
+  mode?: 'dom' | 'ssr' | 'hydrate';
+  /** 开发模式。`hydrate` 时生成的代码带上模板的源码位置，运行时发现和服务端渲染的 DOM 不匹配时报告出错的位置。 */
+  dev?: boolean;
+}
+export interface BindingAnalyzeOptions
+  extends Omit<BindingTransformOptions, 'failOnError' | 'mode' | 'dev'> {
+  /** 检查国际化 t 函数的用法。 */
+  intl?: boolean;
+}
+export type DiagnosticSeverity = 'error' | 'warning' | 'hint';
+/** 结构化的诊断信息，行号和列号都从 1 开始。 */
+export interface CompileDiagnostic {
+  /** 稳定的诊断编号，例如 `JG2104`。语法错误等非编译器产生的诊断没有编号。 */
+  code: string | null;
+  severity: DiagnosticSeverity;
+  message: string;
+  line: number;
+  column: number;
+  endLine: number;
+  endColumn: number;
+}
 /** rust binding compiler interface */
 export interface JingeCompiler {
   transform(
@@ -11,10 +55,24 @@ export interface JingeCompiler {
     code: string,
     sourcemap: boolean,
     intl: number,
+    options?: BindingTransformOptions,
   ): {
     code: string;
     parsedComponents: string;
     map?: string | null;
+    diagnostics: CompileDiagnostic[];
+  };
+  /**
+   * 只做检查不生成代码，返回诊断信息和解析到的组件，适用于编辑器插件、lint 等场景。
+   * 只有 `.tsx` 文件会检查模板。
+   */
+  analyze(
+    filename: string,
+    code: string,
+    options?: BindingAnalyzeOptions,
+  ): {
+    parsedComponents: string[];
+    diagnostics: CompileDiagnostic[];
   };
 }
 
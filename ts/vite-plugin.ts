@@ -1,4 +1,5 @@
 import type { PluginOption } from 'vite';
+import type { BindingTransformOptions, DiagnosticLang } from './binding.js';
 import { loadBinding } from './binding.js';
 
 export interface JingeVitePluginOptions {
@@ -16,6 +17,32 @@ export interface JingeVitePluginOptions {
    * 除 `jinge` 库外，这个参数还会对 `jinge-router` 库以同样的作用生效。
    */
   importAlias?: 'source' | 'dev';
+  /**
+   * 编译诊断信息的语言。未指定时读取环境变量 `JINGE_COMPILER_LANG`，默认为 `zh`。
+   */
+  lang?: DiagnosticLang;
+  /**
+   * 将全部 warning 提升为 error，通常用于 CI。
+   */
+  warningsAsErrors?: boolean;
+  /**
+   * 存在 error 级别的诊断时中断编译。
+   */
+  failOnError?: boolean;
+  /**
+   * 按诊断编号调整严重级别或关闭诊断，例如 `{ JG1002: 'off' }`。
+   * 也可以在源码中使用 `// jinge-ignore JG1002` 注释忽略该行和下一行的诊断。
+   */
+  rules?: BindingTransformOptions['rules'];
+  /**
+   * 高阶函数组件的包装函数白名单，例如 `['withTheme']`。
+   */
+  hocWrappers?: string[];
+  /**
+   * 页面由服务端渲染时，客户端的模块编译为 hydrate 模式，接管服务端渲染出的 DOM 而不是重新创建。
+   * 服务端渲染的模块（vite 的 ssr 编译）总是编译为 ssr 模式。
+   */
+  hydrate?: boolean;
   /**
    * 打开国际化多语言功能。
    */
@@ -64,10 +91,12 @@ function getAliasConfig(importAlias?: 'source' | 'dev') {
 }
 export function jingeVitePlugin(options?: JingeVitePluginOptions): PluginOption {
   let hmrEnabled = false;
+  let devEnabled = false;
   let intlOpts: { dropDefaultText?: boolean } | null = null;
   let sourcemapEnabled = true;
   let base = '';
-  function transform(code: string, id: string) {
+  /** ssr 为 true 表示 vite 正在为服务端渲染编译模块，模板编译为返回 html 字符串的函数。 */
+  function transform(code: string, id: string, ssr?: boolean) {
     const qi = id.lastIndexOf('?');
     if (qi > 0) id = id.slice(0, qi);
     const type = id.endsWith('.tsx') ? 2 : id.endsWith('.ts') ? 1 : 0;
@@ -79,6 +108,15 @@ export function jingeVitePlugin(options?: JingeVitePluginOptions): PluginOption
       code,
       sourcemapEnabled,
       intlOpts ? (intlOpts.dropDefaultText ? 2 : 1) : 0,
+      {
+        lang: options?.lang,
+        warningsAsErrors: options?.warningsAsErrors,
+        failOnError: options?.failOnError,
+        rules: options?.rules,
+        hocWrappers: options?.hocWrappers,
+        mode: ssr ? 'ssr' : options?.hydrate ? 'hydrate' : 'dom',
+        dev: devEnabled,
+      },
     );
     if (!result.map) result.map = null; // 空字符串转成 null
     return result;
@@ -100,8 +138,8 @@ export function jingeVitePlugin(options?: JingeVitePluginOptions): PluginOption
       config() {
         return getAliasConfig(options?.importAlias);
       },
-      transform(code: string, id: string) {
-        return transform(code, id);
+      transform(code: string, id: string, opts?: { ssr?: boolean }) {
+        return transform(code, id, opts?.ssr);
       },
     },
     {
@@ -115,6 +153,7 @@ export function jingeVitePlugin(options?: JingeVitePluginOptions): PluginOption
       name: 'vite:jinge:sereve',
       apply: 'serve',
       configResolved(config) {
+        devEnabled = true;
         if (config.server.hmr !== false) hmrEnabled = true;
         options?.intl &&
           (intlOpts = {
@@ -138,9 +177,10 @@ export function jingeVitePlugin(options?: JingeVitePluginOptions): PluginOption
           children: `import '${base}/@jinge-hmr-runtime';`,
         },
       ],
-      transform(code: string, id: string) {
-        const result = transform(code, id);
-        if (!result || !hmrEnabled || !result.parsedComponents) return result;
+      transform(code: string, id: string, opts?: { ssr?: boolean }) {
+        const result = transform(code, id, opts?.ssr);
+        // 服务端渲染的模块运行在 node 中，不需要注入 HMR 代码。
+        if (!result || !hmrEnabled || opts?.ssr || !result.parsedComponents) return result;
         const parsedComponents = result.parsedComponents.split(',');
         // console.log(parsedComponents);
         if (!parsedComponents.length) return result;